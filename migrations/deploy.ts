@@ -2,8 +2,8 @@
 // single deploy script that's invoked from the CLI, injecting a provider
 // configured from the workspace's Anchor.toml.
 
-import * as anchor from "@project-serum/anchor";
-import { Program } from "@project-serum/anchor";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
 import { ContestProgram } from "../target/types/contest_program";
 
@@ -25,11 +25,40 @@ async function main() {
   const platformFeePercentage = 10; // 10%
   const minEntryFee = new anchor.BN(100000000); // 0.1 SOL
   const minVoteFee = new anchor.BN(10000000); // 0.01 SOL
+  const feeRecipient = provider.wallet.publicKey; // Wallet receiving platform fees
+
+  const existingConfig = await provider.connection.getAccountInfo(platformConfigPDA);
+
+  if (existingConfig) {
+    if (existingConfig.data.length >= program.account.platformConfig.size) {
+      console.log("Platform configuration is up to date, skipping migration");
+      console.log("Platform config PDA:", platformConfigPDA.toString());
+      return;
+    }
+
+    // Resize a config created by an older program version and set the fee recipient
+    try {
+      await program.methods
+        .migratePlatformConfig(feeRecipient)
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          platformConfig: platformConfigPDA,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Platform configuration migrated successfully!");
+      console.log("Platform config PDA:", platformConfigPDA.toString());
+    } catch (error) {
+      console.error("Error migrating platform configuration:", error);
+    }
+    return;
+  }
 
   try {
     await program.methods
-      .initializePlatform(platformFeePercentage, minEntryFee, minVoteFee)
-      .accounts({
+      .initializePlatform(platformFeePercentage, minEntryFee, minVoteFee, feeRecipient)
+      .accountsPartial({
         authority: provider.wallet.publicKey,
         platformConfig: platformConfigPDA,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -1,17 +1,37 @@
-// Platform constants
-pub const PLATFORM_FEE_PERCENTAGE: u8 = 10;  // 10% platform fee
-
 // Time constraints
 pub const MIN_CONTEST_DURATION: i64 = 60 * 60 * 24;  // 1 day in seconds
 pub const MAX_CONTEST_DURATION: i64 = 60 * 60 * 24 * 30;  // 30 days in seconds
+pub const FINALIZATION_TIMEOUT: i64 = 60 * 60 * 24 * 3;  // 3 days after voting_end_time before anyone can count votes
+pub const CANCELLATION_GRACE_PERIOD: i64 = 60 * 60 * 24 * 7;  // 7 days after voting_end_time before anyone can cancel
+pub const MAX_REVEAL_PERIOD: i64 = 60 * 60 * 24 * 7;  // Longest reveal phase a secret ballot contest can have
+pub const CLAIM_PERIOD: i64 = 60 * 60 * 24 * 30;  // 30 days after finalization or cancellation to claim prizes and refunds
 
 // Contest requirements
 pub const MIN_ENTRIES_FOR_CONTEST: u32 = 3;  // Minimum entries required for a valid contest
+pub const MAX_ENTRIES_PER_CONTEST: u32 = 25;  // count_votes takes every entry in one transaction, so this keeps it under the size limit
+pub const MAX_ENTRIES_PER_PARTICIPANT: usize = 10;  // Most entries one wallet can submit to a contest
+pub const MAX_ENTRY_REVISIONS: usize = 5;  // Previous versions of an entry kept in its revision log
+
+// Prize distribution
+pub const MAX_PRIZE_PLACES: usize = 10;  // Maximum number of places in a prize schedule
+pub const BASIS_POINTS: u64 = 10_000;  // Prize schedule shares are in basis points and sum to this
+
+// Judging
+pub const MAX_JUDGES: usize = 10;  // Maximum number of judges on a contest's panel
+pub const MAX_JUDGE_SCORE: u8 = 100;  // Highest score a judge can give an entry
 
-// Prize distribution percentages - also defined in state.rs
-pub const FIRST_PLACE_PERCENTAGE: u8 = 50;   // 50% to first place
-pub const SECOND_PLACE_PERCENTAGE: u8 = 30;  // 30% to second place
-pub const THIRD_PLACE_PERCENTAGE: u8 = 20;   // 20% to third place
+// Emergency pause. Each bit of PlatformConfig::paused_instructions pauses a
+// group of instructions; refunds of cancelled contests and disqualified votes
+// and token unlocks are never paused.
+pub const PAUSE_CONTESTS: u32 = 1 << 0;  // create_contest, update_contest, update_contest_status, advance_contest, cancel_contest
+pub const PAUSE_ENTRIES: u32 = 1 << 1;  // submit_entry, update_entry, withdraw_entry, disqualify_entry
+pub const PAUSE_VOTING: u32 = 1 << 2;  // cast_vote, commit_vote, reveal_vote
+pub const PAUSE_JUDGING: u32 = 1 << 3;  // submit_judge_score
+pub const PAUSE_COUNTING: u32 = 1 << 4;  // count_votes
+pub const PAUSE_FUNDING: u32 = 1 << 5;  // add_funds_to_prize_pool
+pub const PAUSE_FEES: u32 = 1 << 6;  // withdraw_platform_fee, sweep_treasury
+pub const PAUSE_PRIZES: u32 = 1 << 7;  // claim_prize, and sweep_treasury so unclaimed prizes stay put while claims are paused
+pub const PAUSE_ALL: u32 = (1 << 8) - 1;  // Every pausable instruction group
 
 // Seeds
 pub const TREASURY_SEED: &[u8] = b"treasury";
@@ -1,9 +1,16 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::constants::{
+    PAUSE_CONTESTS, PAUSE_COUNTING, PAUSE_ENTRIES, PAUSE_FEES, PAUSE_FUNDING, PAUSE_JUDGING,
+    PAUSE_PRIZES, PAUSE_VOTING,
+};
 use crate::state::*;
 use crate::errors::ContestError;
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(platform_fee_percentage: u8, min_entry_fee: u64, min_vote_fee: u64)]
+#[instruction(platform_fee_percentage: u8, min_entry_fee: u64, min_vote_fee: u64, fee_recipient: Pubkey)]
 pub struct InitializePlatform<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -20,6 +27,81 @@ pub struct InitializePlatform<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContestError::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigratePlatformConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Accounts created by older versions cannot be deserialized until they are resized.
+    /// The owner, discriminator and authority are checked in the handler.
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump
+    )]
+    pub platform_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPlatformPause<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContestError::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposePlatformAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContestError::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptPlatformAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = platform_config.pending_authority == Some(new_authority.key()) @ ContestError::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(contest_id: [u8; 32], title: String, description: String)]
 pub struct CreateContest<'info> {
@@ -47,12 +129,30 @@ pub struct CreateContest<'info> {
     #[account(
         seeds = [PLATFORM_CONFIG_SEED],
         bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_CONTESTS) @ ContestError::ProgramPaused
     )]
     pub platform_config: Account<'info, PlatformConfig>,
 
+    // The mint the contest is denominated in, leave out for a SOL contest
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateContest<'info> {
     #[account(mut)]
@@ -66,8 +166,16 @@ pub struct UpdateContest<'info> {
         constraint = contest.status == ContestStatus::Upcoming @ ContestError::ContestAlreadyStarted
     )]
     pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_CONTESTS) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateContestStatus<'info> {
     #[account(mut)]
@@ -80,8 +188,58 @@ pub struct UpdateContestStatus<'info> {
         constraint = contest.authority == authority.key() @ ContestError::Unauthorized
     )]
     pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_CONTESTS) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AdvanceContest<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_CONTESTS) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelContest<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status != ContestStatus::Finalized @ ContestError::InvalidStatusTransition,
+        constraint = contest.status != ContestStatus::Cancelled @ ContestError::InvalidStatusTransition
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_CONTESTS) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SubmitEntry<'info> {
     #[account(mut)]
@@ -95,15 +253,31 @@ pub struct SubmitEntry<'info> {
     )]
     pub contest: Account<'info, ContestAccount>,
 
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_ENTRIES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
     #[account(
         init,
         payer = participant,
         space = 8 + EntryAccount::SIZE,
-        seeds = [ENTRY_SEED, contest.key().as_ref(), participant.key().as_ref()],
+        seeds = [ENTRY_SEED, contest.key().as_ref(), contest.next_entry_index.to_le_bytes().as_ref()],
         bump
     )]
     pub entry: Account<'info, EntryAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + ParticipantRecord::SIZE,
+        seeds = [PARTICIPANT_SEED, contest.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub participant_record: Account<'info, ParticipantRecord>,
+
     #[account(
         mut,
         seeds = [TREASURY_SEED, contest.key().as_ref()],
@@ -112,8 +286,33 @@ pub struct SubmitEntry<'info> {
     pub treasury: Account<'info, TreasuryAccount>,
 
     pub system_program: Program<'info, System>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = participant,
+        token::token_program = token_program
+    )]
+    pub participant_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateEntry<'info> {
     #[account(mut)]
@@ -122,48 +321,84 @@ pub struct UpdateEntry<'info> {
     #[account(
         seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
         bump = contest.bump,
-        constraint = contest.status == ContestStatus::Live @ ContestError::EntryPeriodEnded
+        constraint = contest.status == ContestStatus::Live @ ContestError::EntryLocked
     )]
     pub contest: Account<'info, ContestAccount>,
 
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_ENTRIES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
     #[account(
         mut,
-        seeds = [ENTRY_SEED, contest.key().as_ref(), participant.key().as_ref()],
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
         bump = entry.bump,
-        constraint = entry.participant == participant.key() @ ContestError::Unauthorized
+        constraint = entry.participant == participant.key() @ ContestError::Unauthorized,
+        constraint = !entry.is_disqualified() @ ContestError::EntryDisqualified
     )]
     pub entry: Account<'info, EntryAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + EntryRevisions::SIZE,
+        seeds = [ENTRY_REVISIONS_SEED, entry.key().as_ref()],
+        bump
+    )]
+    pub entry_revisions: Account<'info, EntryRevisions>,
+
+    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CastVote<'info> {
+pub struct WithdrawEntry<'info> {
     #[account(mut)]
-    pub voter: Signer<'info>,
+    pub participant: Signer<'info>,
 
     #[account(
         mut,
         seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
         bump = contest.bump,
-        constraint = contest.status == ContestStatus::Voting @ ContestError::VotingPeriodEnded
+        constraint = contest.status == ContestStatus::Live @ ContestError::EntryPeriodEnded
     )]
     pub contest: Account<'info, ContestAccount>,
 
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_ENTRIES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
     #[account(
         mut,
-        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.participant.as_ref()],
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
         bump = entry.bump,
-        constraint = entry.contest == contest.key()
+        constraint = entry.participant == participant.key() @ ContestError::Unauthorized,
+        constraint = !entry.is_disqualified() @ ContestError::EntryDisqualified,
+        close = participant
     )]
     pub entry: Account<'info, EntryAccount>,
 
+    // The revision log of an entry that was ever updated, closed along with it
     #[account(
-        init,
-        payer = voter,
-        space = 8 + VoteAccount::SIZE,
-        seeds = [VOTE_SEED, voter.key().as_ref(), entry.key().as_ref()],
-        bump
+        mut,
+        seeds = [ENTRY_REVISIONS_SEED, entry.key().as_ref()],
+        bump = entry_revisions.bump,
+        close = participant
     )]
-    pub vote: Account<'info, VoteAccount>,
+    pub entry_revisions: Option<Account<'info, EntryRevisions>>,
+
+    #[account(
+        mut,
+        seeds = [PARTICIPANT_SEED, contest.key().as_ref(), participant.key().as_ref()],
+        bump = participant_record.bump
+    )]
+    pub participant_record: Account<'info, ParticipantRecord>,
 
     #[account(
         mut,
@@ -172,35 +407,72 @@ pub struct CastVote<'info> {
     )]
     pub treasury: Account<'info, TreasuryAccount>,
 
-    pub system_program: Program<'info, System>,
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = participant,
+        token::token_program = token_program
+    )]
+    pub participant_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CountVotes<'info> {
-    #[account(mut)]
+pub struct DisqualifyEntry<'info> {
     pub authority: Signer<'info>,
 
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_ENTRIES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    // Either the contest creator or the platform authority can disqualify,
+    // up until the ranking is decided
     #[account(
         mut,
         seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
         bump = contest.bump,
-        constraint = contest.authority == authority.key() @ ContestError::Unauthorized,
-        constraint = contest.status == ContestStatus::Closed @ ContestError::ContestNotEnded
+        constraint = authority.key() == contest.authority
+            || authority.key() == platform_config.authority @ ContestError::Unauthorized,
+        constraint = matches!(
+            contest.status,
+            ContestStatus::Live | ContestStatus::Voting | ContestStatus::Revealing | ContestStatus::Closed
+        ) && !contest.winners_decided @ ContestError::DisqualificationClosed
     )]
     pub contest: Account<'info, ContestAccount>,
-}
 
-#[derive(Accounts)]
-pub struct AddFundsToPrizePool<'info> {
-    #[account(mut)]
-    pub contributor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        constraint = !entry.is_disqualified() @ ContestError::EntryDisqualified
+    )]
+    pub entry: Account<'info, EntryAccount>,
 
+    /// CHECK: Must be the participant who submitted the entry, receives a refunded entry fee
     #[account(
         mut,
-        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
-        bump = contest.bump
+        address = entry.participant @ ContestError::Unauthorized
     )]
-    pub contest: Account<'info, ContestAccount>,
+    pub participant: AccountInfo<'info>,
 
     #[account(
         mut,
@@ -209,46 +481,777 @@ pub struct AddFundsToPrizePool<'info> {
     )]
     pub treasury: Account<'info, TreasuryAccount>,
 
-    pub system_program: Program<'info, System>,
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = participant,
+        token::token_program = token_program
+    )]
+    pub participant_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct DistributePrizes<'info> {
+pub struct CastVote<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub voter: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
         bump = contest.bump,
-        constraint = contest.authority == authority.key() @ ContestError::Unauthorized,
-        constraint = contest.status == ContestStatus::Finalized @ ContestError::ContestNotEnded,
-        constraint = contest.winners_decided @ ContestError::WinnersNotDecided
+        constraint = contest.status == ContestStatus::Voting @ ContestError::VotingPeriodEnded
     )]
     pub contest: Account<'info, ContestAccount>,
 
     #[account(
-        mut,
-        seeds = [TREASURY_SEED, contest.key().as_ref()],
-        bump,
-        constraint = !treasury.is_distributed @ ContestError::PrizesAlreadyDistributed
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_VOTING) @ ContestError::ProgramPaused
     )]
-    pub treasury: Account<'info, TreasuryAccount>,
+    pub platform_config: Account<'info, PlatformConfig>,
 
-    /// CHECK: This is the platform wallet that receives the platform fee
-    #[account(mut)]
-    pub platform_wallet: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        constraint = entry.contest == contest.key(),
+        constraint = !entry.is_disqualified() @ ContestError::EntryDisqualified
+    )]
+    pub entry: Account<'info, EntryAccount>,
 
-    /// CHECK: This is the first place winner
-    #[account(mut)]
-    pub first_place_recipient: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteAccount::SIZE,
+        seeds = [VOTE_SEED, voter.key().as_ref(), entry.key().as_ref()],
+        bump
+    )]
+    pub vote: Account<'info, VoteAccount>,
 
-    /// CHECK: This is the second place winner
-    #[account(mut)]
-    pub second_place_recipient: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = 8 + VoterRecord::SIZE,
+        seeds = [VOTE_SEED, contest.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
 
-    /// CHECK: This is the third place winner
-    #[account(mut)]
-    pub third_place_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
 
     pub system_program: Program<'info, System>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = voter,
+        token::token_program = token_program
+    )]
+    pub voter_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Governance token accounts, only required for token lock voting
+    #[account(
+        constraint = contest.rules.voting_mode.governance_mint() == Some(governance_mint.key())
+            @ ContestError::InvalidGovernanceMint
+    )]
+    pub governance_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = governance_mint,
+        token::authority = voter,
+        token::token_program = governance_token_program
+    )]
+    pub voter_governance_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init,
+        payer = voter,
+        seeds = [VOTE_ESCROW_SEED, vote.key().as_ref()],
+        bump,
+        token::mint = governance_mint,
+        token::authority = vote_escrow,
+        token::token_program = governance_token_program
+    )]
+    pub vote_escrow: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub governance_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct CommitVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status == ContestStatus::Voting @ ContestError::VotingPeriodEnded
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_VOTING) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteAccount::SIZE,
+        seeds = [VOTE_SEED, voter.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub vote: Account<'info, VoteAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = 8 + VoterRecord::SIZE,
+        seeds = [VOTE_SEED, contest.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = voter,
+        token::token_program = token_program
+    )]
+    pub voter_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Governance token accounts, only required for token lock voting
+    #[account(
+        constraint = contest.rules.voting_mode.governance_mint() == Some(governance_mint.key())
+            @ ContestError::InvalidGovernanceMint
+    )]
+    pub governance_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = governance_mint,
+        token::authority = voter,
+        token::token_program = governance_token_program
+    )]
+    pub voter_governance_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init,
+        payer = voter,
+        seeds = [VOTE_ESCROW_SEED, vote.key().as_ref()],
+        bump,
+        token::mint = governance_mint,
+        token::authority = vote_escrow,
+        token::token_program = governance_token_program
+    )]
+    pub vote_escrow: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub governance_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealVote<'info> {
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status == ContestStatus::Revealing @ ContestError::InvalidStatusTransition
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_VOTING) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        constraint = entry.contest == contest.key() @ ContestError::InvalidEntryAccount
+    )]
+    pub entry: Account<'info, EntryAccount>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_SEED, voter.key().as_ref(), vote.seed().as_ref()],
+        bump = vote.bump,
+        constraint = vote.contest == contest.key() @ ContestError::Unauthorized,
+        constraint = vote.commitment.is_some() @ ContestError::InvalidVotingMethod,
+        constraint = !vote.revealed @ ContestError::VoteAlreadyRevealed
+    )]
+    pub vote: Account<'info, VoteAccount>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnlockVoteTokens<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = matches!(
+            contest.status,
+            ContestStatus::Finalized | ContestStatus::Cancelled
+        ) @ ContestError::ContestNotEnded
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_SEED, voter.key().as_ref(), vote.seed().as_ref()],
+        bump = vote.bump,
+        constraint = vote.contest == contest.key() @ ContestError::Unauthorized,
+        constraint = vote.locked_amount > 0 @ ContestError::NoLockedVoteTokens
+    )]
+    pub vote: Account<'info, VoteAccount>,
+
+    #[account(
+        mut,
+        constraint = contest.rules.voting_mode.governance_mint() == Some(governance_mint.key())
+            @ ContestError::InvalidGovernanceMint
+    )]
+    pub governance_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = governance_mint,
+        token::authority = voter,
+        token::token_program = governance_token_program
+    )]
+    pub voter_governance_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_ESCROW_SEED, vote.key().as_ref()],
+        bump,
+        token::mint = governance_mint,
+        token::authority = vote_escrow,
+        token::token_program = governance_token_program
+    )]
+    pub vote_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub governance_token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SubmitJudgeScore<'info> {
+    #[account(mut)]
+    pub judge: Signer<'info>,
+
+    #[account(
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.judges.contains(&judge.key()) @ ContestError::NotAJudge,
+        constraint = matches!(
+            contest.status,
+            ContestStatus::Voting | ContestStatus::Revealing | ContestStatus::Closed
+        ) @ ContestError::JudgingClosed,
+        constraint = !contest.winners_decided @ ContestError::JudgingClosed
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_JUDGING) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        constraint = entry.contest == contest.key() @ ContestError::InvalidEntryAccount
+    )]
+    pub entry: Account<'info, EntryAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = judge,
+        space = 8 + JudgeScore::SIZE,
+        seeds = [JUDGE_SCORE_SEED, entry.key().as_ref(), judge.key().as_ref()],
+        bump
+    )]
+    pub judge_score: Account<'info, JudgeScore>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CountVotes<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status == ContestStatus::Closed @ ContestError::ContestNotEnded,
+        constraint = !contest.winners_decided @ ContestError::WinnersAlreadyDecided
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_COUNTING) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddFundsToPrizePool<'info> {
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status != ContestStatus::Cancelled @ ContestError::ContestCancelled,
+        constraint = !matches!(
+            contest.status,
+            ContestStatus::Closed | ContestStatus::Finalized
+        ) @ ContestError::PrizePoolLocked
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_FUNDING) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = contributor,
+        token::token_program = token_program
+    )]
+    pub contributor_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub participant: Signer<'info>,
+
+    #[account(
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status == ContestStatus::Finalized @ ContestError::ContestNotEnded,
+        constraint = contest.winners_decided @ ContestError::WinnersNotDecided
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_PRIZES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        constraint = entry.participant == participant.key() @ ContestError::Unauthorized,
+        constraint = entry.rank.is_some() @ ContestError::InvalidRanking,
+        constraint = !entry.prize_claimed @ ContestError::PrizeAlreadyClaimed
+    )]
+    pub entry: Account<'info, EntryAccount>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        constraint = participant_token_account.owner == participant.key() @ ContestError::InvalidPrizeRecipient,
+        token::token_program = token_program
+    )]
+    pub participant_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawPlatformFee<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status == ContestStatus::Finalized @ ContestError::ContestNotEnded
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump,
+        constraint = !treasury.platform_fee_withdrawn @ ContestError::PlatformFeeAlreadyWithdrawn
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_FEES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: Must be the fee recipient configured on the platform
+    #[account(
+        mut,
+        address = platform_config.fee_recipient @ ContestError::InvalidFeeRecipient
+    )]
+    pub platform_wallet: AccountInfo<'info>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = platform_wallet,
+        token::token_program = token_program
+    )]
+    pub platform_wallet_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepTreasury<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = matches!(
+            contest.status,
+            ContestStatus::Finalized | ContestStatus::Cancelled
+        ) @ ContestError::ContestNotEnded
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump,
+        close = platform_wallet
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.is_paused(PAUSE_FEES | PAUSE_PRIZES) @ ContestError::ProgramPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: Must be the fee recipient configured on the platform
+    #[account(
+        mut,
+        address = platform_config.fee_recipient @ ContestError::InvalidFeeRecipient
+    )]
+    pub platform_wallet: AccountInfo<'info>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        mut,
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = platform_wallet,
+        token::token_program = token_program
+    )]
+    pub platform_wallet_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimEntryRefund<'info> {
+    #[account(mut)]
+    pub participant: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump,
+        constraint = contest.status == ContestStatus::Cancelled @ ContestError::ContestNotCancelled
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, contest.key().as_ref(), entry.index.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        constraint = entry.participant == participant.key() @ ContestError::Unauthorized,
+        constraint = !entry.is_disqualified() @ ContestError::EntryDisqualified,
+        close = participant
+    )]
+    pub entry: Account<'info, EntryAccount>,
+
+    // The revision log of an entry that was ever updated, closed along with it
+    #[account(
+        mut,
+        seeds = [ENTRY_REVISIONS_SEED, entry.key().as_ref()],
+        bump = entry_revisions.bump,
+        close = participant
+    )]
+    pub entry_revisions: Option<Account<'info, EntryRevisions>>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = participant,
+        token::token_program = token_program
+    )]
+    pub participant_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVoteRefund<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    // Refundable once the contest is cancelled, or at any time if the entry
+    // the vote was cast for has been disqualified
+    #[account(
+        mut,
+        seeds = [CONTEST_SEED, contest.contest_id.as_ref()],
+        bump = contest.bump
+    )]
+    pub contest: Account<'info, ContestAccount>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_SEED, voter.key().as_ref(), vote.seed().as_ref()],
+        bump = vote.bump,
+        constraint = vote.contest == contest.key() @ ContestError::Unauthorized,
+        constraint = vote.locked_amount == 0 @ ContestError::VoteTokensLocked,
+        close = voter
+    )]
+    pub vote: Account<'info, VoteAccount>,
+
+    /// CHECK: The entry the vote was cast for (the default address for an
+    /// unrevealed commitment), only read to check whether it was disqualified
+    #[account(address = vote.entry @ ContestError::InvalidEntryAccount)]
+    pub entry: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, contest.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, TreasuryAccount>,
+
+    // Token accounts, only required when the contest is denominated in an SPL token
+    #[account(
+        constraint = contest.fee_mint == Some(fee_mint.key()) @ ContestError::InvalidFeeMint
+    )]
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = voter,
+        token::token_program = token_program
+    )]
+    pub voter_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
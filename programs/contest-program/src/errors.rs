@@ -23,9 +23,6 @@ pub enum ContestError {
     #[msg("このコンテストにはすでに投票しています")]
     AlreadyVoted,
 
-    #[msg("賞金はすでに分配されています")]
-    PrizesAlreadyDistributed,
-
     #[msg("権限がありません")]
     Unauthorized,
 
@@ -41,7 +38,7 @@ pub enum ContestError {
     #[msg("コンテストはまだ終了していません")]
     ContestNotEnded,
 
-    #[msg("ランキングは1-3である必要があります")]
+    #[msg("エントリーのランキングが無効です")]
     InvalidRanking,
 
     #[msg("十分なエントリーがありません")]
@@ -49,4 +46,127 @@ pub enum ContestError {
 
     #[msg("最小エントリー数に達していません")]
     MinimumEntriesNotMet,
-} 
\ No newline at end of file
+
+    #[msg("無効なエントリーアカウント")]
+    InvalidEntryAccount,
+
+    #[msg("渡されたエントリー数がコンテストのエントリー数と一致しません")]
+    EntryCountMismatch,
+
+    #[msg("同じエントリーが複数回渡されています")]
+    DuplicateEntry,
+
+    #[msg("勝者はすでに決定されています")]
+    WinnersAlreadyDecided,
+
+    #[msg("賞金の受取人がエントリーの参加者と一致しません")]
+    InvalidPrizeRecipient,
+
+    #[msg("手数料の受取アドレスがプラットフォーム設定と一致しません")]
+    InvalidFeeRecipient,
+
+    #[msg("無効なプラットフォーム設定アカウント")]
+    InvalidPlatformConfig,
+
+    #[msg("コンテストは中止されていません")]
+    ContestNotCancelled,
+
+    #[msg("コンテストを中止する権限がないか、猶予期間が経過していません")]
+    CancellationNotAllowed,
+
+    #[msg("コンテストは中止されています")]
+    ContestCancelled,
+
+    #[msg("エントリー期間はまだ終了していません")]
+    EntryPeriodNotEnded,
+
+    #[msg("無効なコンテストルール")]
+    InvalidContestRules,
+
+    #[msg("賞金プールは確定しているため資金を追加できません")]
+    PrizePoolLocked,
+
+    #[msg("このエントリーの賞金はすでに受け取られています")]
+    PrizeAlreadyClaimed,
+
+    #[msg("プラットフォーム手数料はすでに引き出されています")]
+    PlatformFeeAlreadyWithdrawn,
+
+    #[msg("受取期限はまだ終了していません")]
+    ClaimPeriodNotEnded,
+
+    #[msg("受取期限が過ぎています")]
+    ClaimPeriodEnded,
+
+    #[msg("トークンのMintがコンテストの設定と一致しません")]
+    InvalidFeeMint,
+
+    #[msg("トークン建てのコンテストに必要なトークンアカウントが渡されていません")]
+    MissingFeeTokenAccounts,
+
+    #[msg("ガバナンストークンのMintがコンテストの設定と一致しません")]
+    InvalidGovernanceMint,
+
+    #[msg("トークンロック投票に必要なアカウントが渡されていません")]
+    MissingGovernanceTokenAccounts,
+
+    #[msg("ロック中のガバナンストークンを先に引き出す必要があります")]
+    VoteTokensLocked,
+
+    #[msg("ロック中のガバナンストークンはありません")]
+    NoLockedVoteTokens,
+
+    #[msg("ロックするトークン量は0より大きい必要があります")]
+    InvalidLockAmount,
+
+    #[msg("票数が多すぎます")]
+    VoteAmountTooLarge,
+
+    #[msg("このコンテストではこの投票方法は使用できません")]
+    InvalidVotingMethod,
+
+    #[msg("公開された投票がコミットメントと一致しません")]
+    InvalidCommitment,
+
+    #[msg("この投票はすでに公開されています")]
+    VoteAlreadyRevealed,
+
+    #[msg("公開期間が終了しています")]
+    RevealPeriodEnded,
+
+    #[msg("審査員ではありません")]
+    NotAJudge,
+
+    #[msg("審査スコアが無効です")]
+    InvalidJudgeScore,
+
+    #[msg("審査の受付期間外です")]
+    JudgingClosed,
+
+    #[msg("エントリー数の上限に達しています")]
+    EntryLimitReached,
+
+    #[msg("コンテンツハッシュが無効です")]
+    InvalidContentHash,
+
+    #[msg("投票開始後はエントリーを変更できません")]
+    EntryLocked,
+
+    #[msg("このエントリーは失格になっています")]
+    EntryDisqualified,
+
+    #[msg("失格処分の受付期間外です")]
+    DisqualificationClosed,
+
+    #[msg("プラットフォームは一時停止中です")]
+    ProgramPaused,
+
+    #[msg("停止する命令の指定が無効です")]
+    InvalidPauseMask,
+
+    #[msg("コンテストのエントリー数が上限に達しています")]
+    ContestEntryLimitReached,
+
+    #[msg("投票数は0より大きい必要があります")]
+    InvalidVoteAmount,
+}
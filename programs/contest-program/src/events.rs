@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ContestRules, ContestStatus, DisqualificationPolicy};
+
+// プラットフォーム設定が初期化された
+#[event]
+pub struct PlatformInitialized {
+    pub authority: Pubkey,
+    pub platform_fee_percentage: u8,
+    pub min_entry_fee: u64,
+    pub min_vote_fee: u64,
+    pub fee_recipient: Pubkey,
+}
+
+// プラットフォーム設定が更新・移行された
+#[event]
+pub struct PlatformConfigUpdated {
+    pub authority: Pubkey,
+    pub platform_fee_percentage: u8,
+    pub min_entry_fee: u64,
+    pub min_vote_fee: u64,
+    pub fee_recipient: Pubkey,
+}
+
+// プラットフォームの緊急停止状態が変更された
+#[event]
+pub struct PlatformPauseUpdated {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub paused_instructions: u32,
+}
+
+// プラットフォーム管理者の移譲が提案された
+#[event]
+pub struct PlatformAuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+// プラットフォーム管理者の移譲が承認された
+#[event]
+pub struct PlatformAuthorityAccepted {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+// コンテストが作成された
+#[event]
+pub struct ContestCreated {
+    pub contest: Pubkey,
+    pub contest_id: [u8; 32],
+    pub authority: Pubkey,
+    pub title: String,
+    pub start_time: i64,
+    pub entry_end_time: i64,
+    pub voting_end_time: i64,
+    pub entry_fee: u64,
+    pub vote_fee: u64,
+    pub fee_mint: Option<Pubkey>,
+    pub platform_fee_percentage: u8,
+    pub rules: ContestRules,
+    pub judges: Vec<Pubkey>,
+}
+
+// コンテスト情報が更新された
+#[event]
+pub struct ContestUpdated {
+    pub contest: Pubkey,
+    pub title: String,
+    pub start_time: i64,
+    pub entry_end_time: i64,
+    pub voting_end_time: i64,
+}
+
+// コンテストのステータスが変更された（中止を含む）
+#[event]
+pub struct StatusChanged {
+    pub contest: Pubkey,
+    pub caller: Pubkey,
+    pub previous_status: ContestStatus,
+    pub new_status: ContestStatus,
+    pub timestamp: i64,
+}
+
+// エントリーが提出された
+#[event]
+pub struct EntrySubmitted {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub index: u32,
+    pub participant: Pubkey,
+    pub content_uri: String,
+    pub content_hash: [u8; 32],
+    pub entry_fee: u64,
+    pub timestamp: i64,
+}
+
+// エントリーが更新された
+#[event]
+pub struct EntryUpdated {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub participant: Pubkey,
+    pub content_uri: String,
+    pub content_hash: [u8; 32],
+    pub revision: u32,                  // 更新後のリビジョン番号
+}
+
+// 投票された
+#[event]
+pub struct VoteCast {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub voter: Pubkey,
+    pub token_amount: u64,
+    pub vote_fee: u64,
+    pub weighted_votes: u64,
+    pub timestamp: i64,
+}
+
+// 秘密投票がコミットされた（投票先のエントリーは公開まで非公開）
+#[event]
+pub struct VoteCommitted {
+    pub contest: Pubkey,
+    pub vote: Pubkey,
+    pub voter: Pubkey,
+    pub vote_fee: u64,
+    pub timestamp: i64,
+}
+
+// 秘密投票が公開され、エントリーに反映された
+#[event]
+pub struct VoteRevealed {
+    pub contest: Pubkey,
+    pub vote: Pubkey,
+    pub entry: Pubkey,
+    pub voter: Pubkey,
+    pub token_amount: u64,
+    pub weighted_votes: u64,
+    pub timestamp: i64,
+}
+
+// 投票開始前にエントリーが取り下げられた
+#[event]
+pub struct EntryWithdrawn {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub participant: Pubkey,
+    pub refund: u64,
+}
+
+// エントリーが失格になった（参加費はコンテストの方針に従って返金・没収される）
+#[event]
+pub struct EntryDisqualified {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub participant: Pubkey,
+    pub authority: Pubkey,
+    pub reason: u8,
+    pub policy: DisqualificationPolicy,
+    pub entry_fee: u64,
+    pub refundable_vote_fees: u64,      // 投票者に返金される投票料金の合計
+}
+
+// 審査員がエントリーを採点した（再採点を含む）
+#[event]
+pub struct JudgeScoreSubmitted {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub judge: Pubkey,
+    pub score: u8,
+    pub judge_score: u64,               // エントリーの審査員スコア合計
+}
+
+// ロックしていたガバナンストークンが投票者に返却された
+#[event]
+pub struct VoteTokensUnlocked {
+    pub contest: Pubkey,
+    pub vote: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+}
+
+// 賞金プールに資金が追加された
+#[event]
+pub struct FundsAdded {
+    pub contest: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub prize_pool: u64,
+}
+
+// 投票が集計され、ランキングが確定した（winnersは順位順のエントリー）
+#[event]
+pub struct VotesCounted {
+    pub contest: Pubkey,
+    pub caller: Pubkey,
+    pub winners: Vec<Pubkey>,
+}
+
+// 入賞したエントリーの参加者が賞金を受け取った
+#[event]
+pub struct PrizeClaimed {
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub participant: Pubkey,
+    pub rank: u8,
+    pub amount: u64,
+}
+
+// プラットフォーム手数料が引き出された
+#[event]
+pub struct PlatformFeeWithdrawn {
+    pub contest: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub amount: u64,
+}
+
+// 受取期限後にトレジャリーの残高が回収され、アカウントが閉じられた
+#[event]
+pub struct TreasurySwept {
+    pub contest: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,                    // 回収したlamports（トークン建てではトークン量）
+}
+
+// 中止されたコンテストの参加費・投票料金が返金された
+#[event]
+pub struct RefundClaimed {
+    pub contest: Pubkey,
+    pub account: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
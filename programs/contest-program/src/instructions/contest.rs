@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 
 use crate::constants::*;
 use crate::context::*;
 use crate::errors::ContestError;
+use crate::events::*;
 use crate::state::*;
 
 pub fn initialize_platform(
@@ -11,6 +11,7 @@ pub fn initialize_platform(
     platform_fee_percentage: u8,
     min_entry_fee: u64,
     min_vote_fee: u64,
+    fee_recipient: Pubkey,
 ) -> Result<()> {
     require!(
         platform_fee_percentage <= 100,
@@ -23,19 +24,35 @@ pub fn initialize_platform(
     platform_config.min_entry_fee = min_entry_fee;
     platform_config.min_vote_fee = min_vote_fee;
     platform_config.bump = ctx.bumps.platform_config;
+    platform_config.pending_authority = None;
+    platform_config.fee_recipient = fee_recipient;
+    platform_config.paused = false;
+    platform_config.paused_instructions = 0;
+
+    emit_cpi!(PlatformInitialized {
+        authority: platform_config.authority,
+        platform_fee_percentage,
+        min_entry_fee,
+        min_vote_fee,
+        fee_recipient,
+    });
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_contest(
     ctx: Context<CreateContest>,
     contest_id: [u8; 32],
     title: String,
     description: String,
     start_time: i64,
-    end_time: i64,
+    entry_end_time: i64,
+    voting_end_time: i64,
     entry_fee: u64,
     vote_fee: u64,
+    rules: ContestRules,
+    judges: Vec<Pubkey>,
 ) -> Result<()> {
     let clock = Clock::get()?;
     let current_time = clock.unix_timestamp;
@@ -45,29 +62,54 @@ pub fn create_contest(
         start_time > current_time,
         ContestError::InvalidTimestamp
     );
+    validate_schedule(start_time, entry_end_time, voting_end_time)?;
+
+    // Validate contest rules
     require!(
-        end_time > start_time,
-        ContestError::InvalidTimestamp
+        rules.max_votes_per_voter > 0,
+        ContestError::InvalidContestRules
     );
     require!(
-        end_time - start_time >= MIN_CONTEST_DURATION,
-        ContestError::InvalidTimestamp
+        (1..=MAX_ENTRIES_PER_PARTICIPANT).contains(&(rules.max_entries_per_participant as usize)),
+        ContestError::InvalidContestRules
     );
     require!(
-        end_time - start_time <= MAX_CONTEST_DURATION,
-        ContestError::InvalidTimestamp
+        (rules.withdrawal_refund_bps as u64) <= BASIS_POINTS,
+        ContestError::InvalidContestRules
     );
-
-    // Validate fees against platform minimums
-    let platform_config = &ctx.accounts.platform_config;
+    validate_prize_schedule(&rules.prize_schedule)?;
     require!(
-        entry_fee >= platform_config.min_entry_fee,
-        ContestError::InsufficientEntryFee
+        (0..=MAX_REVEAL_PERIOD).contains(&rules.reveal_period),
+        ContestError::InvalidContestRules
     );
+    // A sealed vote can't be tied to earlier votes for the same entry, so a
+    // secret ballot can't price votes quadratically per voter per entry
     require!(
-        vote_fee >= platform_config.min_vote_fee,
-        ContestError::InsufficientVoteFee
+        !(rules.is_secret_ballot() && rules.voting_mode == VotingMode::Quadratic),
+        ContestError::InvalidContestRules
     );
+    validate_judges(&judges, rules.judge_weight_bps)?;
+
+    // A token contest needs the treasury's token account to receive fees
+    let fee_mint = ctx.accounts.fee_mint.as_ref().map(|mint| mint.key());
+    require!(
+        fee_mint.is_none() || ctx.accounts.treasury_token_account.is_some(),
+        ContestError::MissingFeeTokenAccounts
+    );
+
+    // Validate fees against platform minimums, which are set in lamports
+    // and therefore only apply to SOL contests
+    let platform_config = &ctx.accounts.platform_config;
+    if fee_mint.is_none() {
+        require!(
+            entry_fee >= platform_config.min_entry_fee,
+            ContestError::InsufficientEntryFee
+        );
+        require!(
+            vote_fee >= platform_config.min_vote_fee,
+            ContestError::InsufficientVoteFee
+        );
+    }
 
     // Initialize contest account
     let contest = &mut ctx.accounts.contest;
@@ -76,15 +118,23 @@ pub fn create_contest(
     contest.title = title;
     contest.description = description;
     contest.start_time = start_time;
-    contest.end_time = end_time;
+    contest.entry_end_time = entry_end_time;
+    contest.voting_end_time = voting_end_time;
     contest.entry_fee = entry_fee;
     contest.vote_fee = vote_fee;
+    contest.fee_mint = fee_mint;
+    contest.platform_fee_percentage = platform_config.platform_fee_percentage;
+    contest.rules = rules;
     contest.status = ContestStatus::Upcoming;
     contest.treasury = ctx.accounts.treasury.key();
     contest.entry_count = 0;
     contest.vote_count = 0;
     contest.total_prize_pool = 0;
     contest.winners_decided = false;
+    contest.claim_deadline = 0;
+    contest.judges = judges;
+    contest.next_entry_index = 0;
+    contest.disqualified_entry_count = 0;
     contest.bump = ctx.bumps.contest;
 
     // Initialize treasury account
@@ -94,9 +144,27 @@ pub fn create_contest(
     treasury.total_funds = 0;
     treasury.platform_fee = 0;
     treasury.prize_pool = 0;
-    treasury.is_distributed = false;
+    treasury.prizes_claimed = 0;
+    treasury.platform_fee_withdrawn = false;
     treasury.bump = ctx.bumps.treasury;
 
+    let contest = &ctx.accounts.contest;
+    emit_cpi!(ContestCreated {
+        contest: contest.key(),
+        contest_id,
+        authority: contest.authority,
+        title: contest.title.clone(),
+        start_time,
+        entry_end_time,
+        voting_end_time,
+        entry_fee,
+        vote_fee,
+        fee_mint,
+        platform_fee_percentage: contest.platform_fee_percentage,
+        rules: contest.rules.clone(),
+        judges: contest.judges.clone(),
+    });
+
     Ok(())
 }
 
@@ -105,7 +173,8 @@ pub fn update_contest(
     title: Option<String>,
     description: Option<String>,
     start_time: Option<i64>,
-    end_time: Option<i64>,
+    entry_end_time: Option<i64>,
+    voting_end_time: Option<i64>,
 ) -> Result<()> {
     let contest = &mut ctx.accounts.contest;
     let clock = Clock::get()?;
@@ -121,48 +190,33 @@ pub fn update_contest(
         contest.description = new_description;
     }
 
-    // Update start time if provided
-    if let Some(new_start_time) = start_time {
-        require!(
-            new_start_time > current_time,
-            ContestError::InvalidTimestamp
-        );
-        require!(
-            contest.end_time > new_start_time,
-            ContestError::InvalidTimestamp
-        );
-        require!(
-            contest.end_time - new_start_time >= MIN_CONTEST_DURATION,
-            ContestError::InvalidTimestamp
-        );
-        require!(
-            contest.end_time - new_start_time <= MAX_CONTEST_DURATION,
-            ContestError::InvalidTimestamp
-        );
+    // Update schedule if any timestamp is provided
+    if start_time.is_some() || entry_end_time.is_some() || voting_end_time.is_some() {
+        if let Some(new_start_time) = start_time {
+            require!(
+                new_start_time > current_time,
+                ContestError::InvalidTimestamp
+            );
+        }
+
+        let new_start_time = start_time.unwrap_or(contest.start_time);
+        let new_entry_end_time = entry_end_time.unwrap_or(contest.entry_end_time);
+        let new_voting_end_time = voting_end_time.unwrap_or(contest.voting_end_time);
+
+        validate_schedule(new_start_time, new_entry_end_time, new_voting_end_time)?;
+
         contest.start_time = new_start_time;
+        contest.entry_end_time = new_entry_end_time;
+        contest.voting_end_time = new_voting_end_time;
     }
 
-    // Update end time if provided
-    if let Some(new_end_time) = end_time {
-        let start_time = if start_time.is_some() {
-            start_time.unwrap()
-        } else {
-            contest.start_time
-        };
-        require!(
-            new_end_time > start_time,
-            ContestError::InvalidTimestamp
-        );
-        require!(
-            new_end_time - start_time >= MIN_CONTEST_DURATION,
-            ContestError::InvalidTimestamp
-        );
-        require!(
-            new_end_time - start_time <= MAX_CONTEST_DURATION,
-            ContestError::InvalidTimestamp
-        );
-        contest.end_time = new_end_time;
-    }
+    emit_cpi!(ContestUpdated {
+        contest: contest.key(),
+        title: contest.title.clone(),
+        start_time: contest.start_time,
+        entry_end_time: contest.entry_end_time,
+        voting_end_time: contest.voting_end_time,
+    });
 
     Ok(())
 }
@@ -172,15 +226,167 @@ pub fn update_contest_status(
     new_status: ContestStatus,
 ) -> Result<()> {
     let contest = &mut ctx.accounts.contest;
-    let current_status = &contest.status;
-    
+    let clock = Clock::get()?;
+
     // Validate status transition
-    match (current_status, &new_status) {
+    validate_status_transition(contest, &new_status, clock.unix_timestamp)?;
+
+    emit_cpi!(StatusChanged {
+        contest: contest.key(),
+        caller: ctx.accounts.authority.key(),
+        previous_status: contest.status.clone(),
+        new_status: new_status.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    // Update the status
+    contest.set_status(new_status, clock.unix_timestamp);
+    
+    Ok(())
+}
+
+// Permissionless crank that moves the contest to its next status once the
+// stored timestamps allow it, so a contest never depends on its creator
+pub fn advance_contest(
+    ctx: Context<AdvanceContest>,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let clock = Clock::get()?;
+
+    let new_status = match contest.status {
+        ContestStatus::Upcoming => ContestStatus::Live,
+        ContestStatus::Live => ContestStatus::Voting,
+        ContestStatus::Voting if contest.rules.is_secret_ballot() => ContestStatus::Revealing,
+        ContestStatus::Voting | ContestStatus::Revealing => ContestStatus::Closed,
+        ContestStatus::Closed => ContestStatus::Finalized,
+        _ => return Err(ContestError::InvalidStatusTransition.into()),
+    };
+
+    validate_status_transition(contest, &new_status, clock.unix_timestamp)?;
+
+    emit_cpi!(StatusChanged {
+        contest: contest.key(),
+        caller: ctx.accounts.caller.key(),
+        previous_status: contest.status.clone(),
+        new_status: new_status.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    contest.set_status(new_status, clock.unix_timestamp);
+
+    Ok(())
+}
+
+pub fn cancel_contest(
+    ctx: Context<CancelContest>,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let caller = ctx.accounts.caller.key();
+    let clock = Clock::get()?;
+    let current_time = clock.unix_timestamp;
+
+    // The creator can cancel before voting starts.
+    // Anyone can cancel a contest that is stuck past its reveal end time plus the grace period,
+    // but not one whose winners are decided, since its prizes are already owed.
+    let cancelled_by_creator = caller == contest.authority
+        && matches!(contest.status, ContestStatus::Upcoming | ContestStatus::Live);
+    let stuck_past_grace_period = !contest.winners_decided
+        && current_time
+            > contest.reveal_end_time().checked_add(CANCELLATION_GRACE_PERIOD).unwrap();
+
+    require!(
+        cancelled_by_creator || stuck_past_grace_period,
+        ContestError::CancellationNotAllowed
+    );
+
+    emit_cpi!(StatusChanged {
+        contest: contest.key(),
+        caller,
+        previous_status: contest.status.clone(),
+        new_status: ContestStatus::Cancelled,
+        timestamp: current_time,
+    });
+
+    contest.set_status(ContestStatus::Cancelled, current_time);
+
+    Ok(())
+}
+
+// Entries are accepted from start_time until entry_end_time,
+// and votes from entry_end_time until voting_end_time
+fn validate_schedule(
+    start_time: i64,
+    entry_end_time: i64,
+    voting_end_time: i64,
+) -> Result<()> {
+    require!(
+        entry_end_time > start_time,
+        ContestError::InvalidTimestamp
+    );
+    require!(
+        voting_end_time > entry_end_time,
+        ContestError::InvalidTimestamp
+    );
+    require!(
+        voting_end_time - start_time >= MIN_CONTEST_DURATION,
+        ContestError::InvalidTimestamp
+    );
+    require!(
+        voting_end_time - start_time <= MAX_CONTEST_DURATION,
+        ContestError::InvalidTimestamp
+    );
+
+    Ok(())
+}
+
+// Every place must receive a share and the shares must add up to the whole pool
+fn validate_prize_schedule(prize_schedule: &[u16]) -> Result<()> {
+    require!(
+        !prize_schedule.is_empty() && prize_schedule.len() <= MAX_PRIZE_PLACES,
+        ContestError::InvalidContestRules
+    );
+    require!(
+        prize_schedule.iter().all(|share| *share > 0),
+        ContestError::InvalidContestRules
+    );
+
+    let total: u64 = prize_schedule.iter().map(|share| *share as u64).sum();
+    require!(total == BASIS_POINTS, ContestError::InvalidContestRules);
+
+    Ok(())
+}
+
+// A judge panel has a bounded number of distinct judges, and judge scores can
+// only carry weight when there is a panel to give them
+fn validate_judges(judges: &[Pubkey], judge_weight_bps: u16) -> Result<()> {
+    require!(judges.len() <= MAX_JUDGES, ContestError::InvalidContestRules);
+    require!(
+        judges
+            .iter()
+            .enumerate()
+            .all(|(index, judge)| !judges[..index].contains(judge)),
+        ContestError::InvalidContestRules
+    );
+    require!(
+        (judge_weight_bps as u64) <= BASIS_POINTS,
+        ContestError::InvalidContestRules
+    );
+    require!(
+        judge_weight_bps == 0 || !judges.is_empty(),
+        ContestError::InvalidContestRules
+    );
+
+    Ok(())
+}
+
+fn validate_status_transition(
+    contest: &ContestAccount,
+    new_status: &ContestStatus,
+    current_time: i64,
+) -> Result<()> {
+    match (&contest.status, new_status) {
         // Upcoming -> Live
         (ContestStatus::Upcoming, ContestStatus::Live) => {
-            let clock = Clock::get()?;
-            let current_time = clock.unix_timestamp;
-            
             require!(
                 contest.start_time <= current_time,
                 ContestError::InvalidTimestamp
@@ -189,20 +395,48 @@ pub fn update_contest_status(
         
         // Live -> Voting
         (ContestStatus::Live, ContestStatus::Voting) => {
-            // Ensure there are enough entries
             require!(
-                contest.entry_count >= MIN_ENTRIES_FOR_CONTEST,
+                contest.entry_end_time <= current_time,
+                ContestError::EntryPeriodNotEnded
+            );
+
+            // Ensure there are enough entries to fill every prize place.
+            // Disqualified entries are never ranked, so they don't count.
+            let prize_places = contest.rules.prize_places() as u32;
+            require!(
+                contest.eligible_entry_count() >= MIN_ENTRIES_FOR_CONTEST.max(prize_places),
                 ContestError::MinimumEntriesNotMet
             );
         },
         
-        // Voting -> Closed
+        // Voting -> Closed, secret ballots must go through the reveal phase
         (ContestStatus::Voting, ContestStatus::Closed) => {
-            let clock = Clock::get()?;
-            let current_time = clock.unix_timestamp;
-            
             require!(
-                contest.end_time <= current_time,
+                !contest.rules.is_secret_ballot(),
+                ContestError::InvalidStatusTransition
+            );
+            require!(
+                contest.voting_end_time <= current_time,
+                ContestError::ContestNotEnded
+            );
+        },
+
+        // Voting -> Revealing
+        (ContestStatus::Voting, ContestStatus::Revealing) => {
+            require!(
+                contest.rules.is_secret_ballot(),
+                ContestError::InvalidStatusTransition
+            );
+            require!(
+                contest.voting_end_time <= current_time,
+                ContestError::ContestNotEnded
+            );
+        },
+
+        // Revealing -> Closed
+        (ContestStatus::Revealing, ContestStatus::Closed) => {
+            require!(
+                contest.reveal_end_time() <= current_time,
                 ContestError::ContestNotEnded
             );
         },
@@ -219,9 +453,6 @@ pub fn update_contest_status(
         // All other transitions are invalid
         _ => return Err(ContestError::InvalidStatusTransition.into()),
     }
-    
-    // Update the status
-    contest.status = new_status;
-    
+
     Ok(())
 }
@@ -1,57 +1,95 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 
+use crate::constants::MAX_ENTRIES_PER_CONTEST;
 use crate::context::*;
 use crate::errors::ContestError;
-use crate::constants::*;
+use crate::events::*;
+use crate::state::{DisqualificationPolicy, EntryAccount, ENTRY_SEED};
+
+use super::treasury::{deposit_to_treasury, fee_token_accounts, pay_from_treasury};
 
 pub fn submit_entry(
     ctx: Context<SubmitEntry>,
     content_uri: String,
+    content_hash: [u8; 32],
 ) -> Result<()> {
     let contest = &ctx.accounts.contest;
     let entry = &mut ctx.accounts.entry;
     let treasury = &mut ctx.accounts.treasury;
-    
-    // Transfer entry fee to treasury
-    let entry_fee = contest.entry_fee;
-    
-    let cpi_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: ctx.accounts.participant.to_account_info(),
-            to: treasury.to_account_info(),
-        },
+    let clock = Clock::get()?;
+
+    // Entries are only accepted inside the entry window
+    require!(
+        clock.unix_timestamp >= contest.start_time && clock.unix_timestamp < contest.entry_end_time,
+        ContestError::EntryPeriodEnded
+    );
+    require!(content_hash != [0; 32], ContestError::InvalidContentHash);
+
+    // count_votes has to fit every entry in a single transaction
+    require!(
+        contest.entry_count < MAX_ENTRIES_PER_CONTEST,
+        ContestError::ContestEntryLimitReached
     );
+
+    // Each participant can submit up to the contest's limit
+    let participant_record = &mut ctx.accounts.participant_record;
+    require!(
+        participant_record.entry_indices.len() < contest.rules.max_entries_per_participant as usize,
+        ContestError::EntryLimitReached
+    );
+
+    if participant_record.entry_indices.is_empty() {
+        participant_record.contest = contest.key();
+        participant_record.participant = ctx.accounts.participant.key();
+        participant_record.bump = ctx.bumps.participant_record;
+    }
+    participant_record.entry_indices.push(contest.next_entry_index);
     
-    system_program::transfer(cpi_context, entry_fee)?;
+    // Transfer entry fee to treasury, recording what the treasury received
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.participant_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    let entry_fee = deposit_to_treasury(
+        &ctx.accounts.participant.to_account_info(),
+        treasury,
+        &ctx.accounts.system_program,
+        fee_token,
+        contest.entry_fee,
+    )?;
 
     // Initialize entry
-    let clock = Clock::get()?;
-    
     entry.contest = contest.key();
     entry.participant = ctx.accounts.participant.key();
     entry.content_uri = content_uri;
+    entry.content_hash = content_hash;
     entry.timestamp = clock.unix_timestamp;
     entry.vote_count = 0;
     entry.weighted_votes = 0;
     entry.rank = None;
+    entry.entry_fee = entry_fee;
+    entry.prize_claimed = false;
+    entry.judge_score = 0;
+    entry.tie_count = 0;
+    entry.index = contest.next_entry_index;
+    entry.vote_fees = 0;
+    entry.vote_platform_fees = 0;
+    entry.disqualification_reason = None;
     entry.bump = ctx.bumps.entry;
 
     // Update contest's entry count
     let contest = &mut ctx.accounts.contest;
     contest.entry_count = contest.entry_count.checked_add(1).unwrap();
+    contest.next_entry_index = contest.next_entry_index.checked_add(1).unwrap();
 
     // Update treasury
     treasury.total_funds = treasury.total_funds.checked_add(entry_fee).unwrap();
     
-    // Split between platform fee and prize pool based on platform fee percentage
-    let platform_fee = entry_fee
-        .checked_mul(PLATFORM_FEE_PERCENTAGE as u64)
-        .unwrap()
-        .checked_div(100)
-        .unwrap();
-    let prize_amount = entry_fee.checked_sub(platform_fee).unwrap();
+    // Split between platform fee and prize pool based on the contest's platform fee percentage
+    let (platform_fee, prize_amount) = contest.split_fee(entry_fee);
     
     treasury.platform_fee = treasury.platform_fee.checked_add(platform_fee).unwrap();
     treasury.prize_pool = treasury.prize_pool.checked_add(prize_amount).unwrap();
@@ -59,16 +97,215 @@ pub fn submit_entry(
     // Update contest's total prize pool
     contest.total_prize_pool = contest.total_prize_pool.checked_add(prize_amount).unwrap();
 
+    emit_cpi!(EntrySubmitted {
+        contest: contest.key(),
+        entry: entry.key(),
+        index: entry.index,
+        participant: entry.participant,
+        content_uri: entry.content_uri.clone(),
+        content_hash,
+        entry_fee,
+        timestamp: entry.timestamp,
+    });
+
     Ok(())
 }
 
-// Update entry function implementation
+// Update entry function implementation. The content is frozen once voting
+// starts so voters always judge what the hash commits to, and every replaced
+// version is kept in the entry's revision log.
 pub fn update_entry(
     ctx: Context<UpdateEntry>,
     content_uri: String,
+    content_hash: [u8; 32],
 ) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        current_time < ctx.accounts.contest.entry_end_time,
+        ContestError::EntryLocked
+    );
+    require!(content_hash != [0; 32], ContestError::InvalidContentHash);
+
     let entry = &mut ctx.accounts.entry;
-    entry.content_uri = content_uri;
+    let entry_revisions = &mut ctx.accounts.entry_revisions;
+
+    // Initialize the revision log on the first update
+    if entry_revisions.entry == Pubkey::default() {
+        entry_revisions.entry = entry.key();
+        entry_revisions.revision_count = 0;
+        entry_revisions.revisions = Vec::new();
+        entry_revisions.bump = ctx.bumps.entry_revisions;
+    }
+
+    let previous_uri = std::mem::replace(&mut entry.content_uri, content_uri);
+    entry_revisions.record(previous_uri, entry.content_hash, current_time);
+    entry.content_hash = content_hash;
+
+    emit_cpi!(EntryUpdated {
+        contest: entry.contest,
+        entry: entry.key(),
+        participant: entry.participant,
+        content_uri: entry.content_uri.clone(),
+        content_hash,
+        revision: entry_revisions.revision_count,
+    });
+
+    Ok(())
+}
+
+// Retracts an entry before voting opens, refunding the contest's configured
+// share of the entry fee. The rest stays in the treasury and is split as if
+// it had been the fee.
+pub fn withdraw_entry(
+    ctx: Context<WithdrawEntry>,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let treasury = &mut ctx.accounts.treasury;
+    let entry = &ctx.accounts.entry;
+
+    require!(
+        Clock::get()?.unix_timestamp < contest.entry_end_time,
+        ContestError::EntryPeriodEnded
+    );
+
+    let entry_fee = entry.entry_fee;
+    let refund = contest.rules.withdrawal_refund(entry_fee);
+
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.participant_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    pay_from_treasury(
+        treasury,
+        &ctx.accounts.participant.to_account_info(),
+        fee_token,
+        refund,
+    )?;
+
+    // Replace the split recorded for the entry fee with the split of what is kept
+    let (platform_fee, prize_amount) = contest.split_fee(entry_fee);
+    let (kept_platform_fee, kept_prize_amount) = contest.split_fee(entry_fee - refund);
+    let platform_fee_refunded = platform_fee - kept_platform_fee;
+    let prize_amount_refunded = prize_amount - kept_prize_amount;
+
+    treasury.total_funds = treasury.total_funds.checked_sub(refund).unwrap();
+    treasury.platform_fee = treasury.platform_fee.checked_sub(platform_fee_refunded).unwrap();
+    treasury.prize_pool = treasury.prize_pool.checked_sub(prize_amount_refunded).unwrap();
+    contest.total_prize_pool = contest.total_prize_pool.checked_sub(prize_amount_refunded).unwrap();
+
+    contest.entry_count = contest.entry_count.checked_sub(1).unwrap();
+
+    // Free the participant's slot so they can submit again
+    let participant_record = &mut ctx.accounts.participant_record;
+    participant_record.entry_indices.retain(|index| *index != entry.index);
+
+    emit_cpi!(EntryWithdrawn {
+        contest: contest.key(),
+        entry: entry.key(),
+        participant: entry.participant,
+        refund,
+    });
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+// Removes an abusive or plagiarized entry from the ranking. The entry fee is
+// refunded, forfeited to the prize pool or slashed to the platform as the
+// contest's rules choose, and the fees of the votes the entry received are
+// taken out of the split so each voter can reclaim theirs.
+pub fn disqualify_entry(
+    ctx: Context<DisqualifyEntry>,
+    reason: u8,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let treasury = &mut ctx.accounts.treasury;
+    let entry = &mut ctx.accounts.entry;
+
+    let entry_fee = entry.entry_fee;
+    let policy = contest.rules.disqualification_policy.clone();
+    let (platform_fee, prize_amount) = contest.split_fee(entry_fee);
+
+    match policy {
+        DisqualificationPolicy::Refund => {
+            let fee_token = fee_token_accounts(
+                contest,
+                &ctx.accounts.fee_mint,
+                &ctx.accounts.participant_token_account,
+                &mut ctx.accounts.treasury_token_account,
+                &ctx.accounts.token_program,
+            )?;
+            pay_from_treasury(
+                treasury,
+                &ctx.accounts.participant.to_account_info(),
+                fee_token,
+                entry_fee,
+            )?;
+
+            treasury.total_funds = treasury.total_funds.checked_sub(entry_fee).unwrap();
+            treasury.platform_fee = treasury.platform_fee.checked_sub(platform_fee).unwrap();
+            treasury.prize_pool = treasury.prize_pool.checked_sub(prize_amount).unwrap();
+            contest.total_prize_pool = contest.total_prize_pool.checked_sub(prize_amount).unwrap();
+        }
+        DisqualificationPolicy::Forfeit => {
+            treasury.platform_fee = treasury.platform_fee.checked_sub(platform_fee).unwrap();
+            treasury.prize_pool = treasury.prize_pool.checked_add(platform_fee).unwrap();
+            contest.total_prize_pool = contest.total_prize_pool.checked_add(platform_fee).unwrap();
+        }
+        DisqualificationPolicy::Slash => {
+            treasury.prize_pool = treasury.prize_pool.checked_sub(prize_amount).unwrap();
+            treasury.platform_fee = treasury.platform_fee.checked_add(prize_amount).unwrap();
+            contest.total_prize_pool = contest.total_prize_pool.checked_sub(prize_amount).unwrap();
+        }
+    }
+
+    // The vote fees stay in the treasury's total funds until the voters
+    // claim them back through claim_vote_refund
+    let vote_prize_amount = entry.vote_fees.checked_sub(entry.vote_platform_fees).unwrap();
+    treasury.platform_fee = treasury.platform_fee.checked_sub(entry.vote_platform_fees).unwrap();
+    treasury.prize_pool = treasury.prize_pool.checked_sub(vote_prize_amount).unwrap();
+    contest.total_prize_pool = contest.total_prize_pool.checked_sub(vote_prize_amount).unwrap();
+
+    entry.disqualification_reason = Some(reason);
+    contest.disqualified_entry_count = contest.disqualified_entry_count.checked_add(1).unwrap();
+
+    emit_cpi!(EntryDisqualified {
+        contest: contest.key(),
+        entry: entry.key(),
+        participant: entry.participant,
+        authority: ctx.accounts.authority.key(),
+        reason,
+        policy,
+        entry_fee,
+        refundable_vote_fees: entry.vote_fees,
+    });
+
+    Ok(())
+}
+
+// Loads an entry passed through remaining_accounts and checks that it is an
+// entry of the given contest living at its canonical PDA
+pub(crate) fn load_entry<'info>(
+    account_info: &'info AccountInfo<'info>,
+    contest_key: &Pubkey,
+) -> Result<Account<'info, EntryAccount>> {
+    // Deserializing checks the owner and the account discriminator
+    let entry = Account::<EntryAccount>::try_from(account_info)?;
+    require_keys_eq!(entry.contest, *contest_key, ContestError::InvalidEntryAccount);
+
+    let expected_key = Pubkey::create_program_address(
+        &[
+            ENTRY_SEED,
+            contest_key.as_ref(),
+            &entry.index.to_le_bytes(),
+            &[entry.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ContestError::InvalidEntryAccount))?;
+    require_keys_eq!(expected_key, account_info.key(), ContestError::InvalidEntryAccount);
+
+    Ok(entry)
+}
@@ -2,8 +2,10 @@ pub mod contest;
 pub mod entry;
 pub mod voting;
 pub mod treasury;
+pub mod platform;
 
 pub use contest::*;
 pub use entry::*;
 pub use voting::*;
 pub use treasury::*;
+pub use platform::*;
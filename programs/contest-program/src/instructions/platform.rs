@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::constants::PAUSE_ALL;
+use crate::context::*;
+use crate::errors::ContestError;
+use crate::events::*;
+use crate::state::PlatformConfig;
+
+pub fn update_platform_config(
+    ctx: Context<UpdatePlatformConfig>,
+    platform_fee_percentage: Option<u8>,
+    min_entry_fee: Option<u64>,
+    min_vote_fee: Option<u64>,
+    fee_recipient: Option<Pubkey>,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+
+    // Update platform fee percentage if provided
+    // Existing contests keep the percentage snapshotted at creation
+    if let Some(new_platform_fee_percentage) = platform_fee_percentage {
+        require!(
+            new_platform_fee_percentage <= 100,
+            ContestError::InvalidFeePercentage
+        );
+        platform_config.platform_fee_percentage = new_platform_fee_percentage;
+    }
+
+    // Update minimum entry fee if provided
+    if let Some(new_min_entry_fee) = min_entry_fee {
+        platform_config.min_entry_fee = new_min_entry_fee;
+    }
+
+    // Update minimum vote fee if provided
+    if let Some(new_min_vote_fee) = min_vote_fee {
+        platform_config.min_vote_fee = new_min_vote_fee;
+    }
+
+    // Update fee recipient if provided
+    if let Some(new_fee_recipient) = fee_recipient {
+        platform_config.fee_recipient = new_fee_recipient;
+    }
+
+    emit_cpi!(PlatformConfigUpdated {
+        authority: platform_config.authority,
+        platform_fee_percentage: platform_config.platform_fee_percentage,
+        min_entry_fee: platform_config.min_entry_fee,
+        min_vote_fee: platform_config.min_vote_fee,
+        fee_recipient: platform_config.fee_recipient,
+    });
+
+    Ok(())
+}
+
+// Resizes a platform config created by an older version of the program to the
+// current layout and sets the fee recipient. New fields are zero-initialized.
+// Does nothing if the account already has the current layout.
+pub fn migrate_platform_config(
+    ctx: Context<MigratePlatformConfig>,
+    fee_recipient: Pubkey,
+) -> Result<()> {
+    let platform_config_info = ctx.accounts.platform_config.to_account_info();
+
+    require_keys_eq!(
+        *platform_config_info.owner,
+        crate::ID,
+        ContestError::InvalidPlatformConfig
+    );
+
+    // The discriminator and the authority are at the same offsets in every layout
+    {
+        let data = platform_config_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 + 32 && data[..8] == *PlatformConfig::DISCRIMINATOR,
+            ContestError::InvalidPlatformConfig
+        );
+        let authority = Pubkey::try_from(&data[8..40]).unwrap();
+        require_keys_eq!(
+            authority,
+            ctx.accounts.authority.key(),
+            ContestError::Unauthorized
+        );
+    }
+
+    // An account already at the current size has nothing to migrate; the fee
+    // recipient of a migrated config can only change via update_platform_config
+    let new_len = 8 + PlatformConfig::SIZE;
+    if platform_config_info.data_len() >= new_len {
+        return Ok(());
+    }
+
+    // Top up rent for the larger account
+    let required_lamports = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(platform_config_info.lamports());
+
+    if required_lamports > 0 {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: platform_config_info.clone(),
+            },
+        );
+
+        system_program::transfer(cpi_context, required_lamports)?;
+    }
+
+    platform_config_info.realloc(new_len, true)?;
+
+    let platform_config = {
+        let mut data = platform_config_info.try_borrow_mut_data()?;
+        let mut platform_config = PlatformConfig::try_deserialize(&mut &data[..])?;
+        platform_config.fee_recipient = fee_recipient;
+        platform_config.try_serialize(&mut &mut data[..])?;
+        platform_config
+    };
+
+    emit_cpi!(PlatformConfigUpdated {
+        authority: platform_config.authority,
+        platform_fee_percentage: platform_config.platform_fee_percentage,
+        min_entry_fee: platform_config.min_entry_fee,
+        min_vote_fee: platform_config.min_vote_fee,
+        fee_recipient: platform_config.fee_recipient,
+    });
+
+    Ok(())
+}
+
+// Pauses the program in an emergency, either entirely or per instruction
+// group. Refunds stay available so users can always get their funds out, and
+// the treasury can't be swept while prize claims are paused.
+pub fn set_platform_pause(
+    ctx: Context<SetPlatformPause>,
+    paused: bool,
+    paused_instructions: u32,
+) -> Result<()> {
+    require!(
+        paused_instructions & !PAUSE_ALL == 0,
+        ContestError::InvalidPauseMask
+    );
+
+    let platform_config = &mut ctx.accounts.platform_config;
+    platform_config.paused = paused;
+    platform_config.paused_instructions = paused_instructions;
+
+    emit_cpi!(PlatformPauseUpdated {
+        authority: platform_config.authority,
+        paused,
+        paused_instructions,
+    });
+
+    Ok(())
+}
+
+pub fn propose_platform_authority(
+    ctx: Context<ProposePlatformAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    // The transfer only takes effect once the new authority accepts it
+    let platform_config = &mut ctx.accounts.platform_config;
+    platform_config.pending_authority = Some(new_authority);
+
+    emit_cpi!(PlatformAuthorityProposed {
+        authority: platform_config.authority,
+        pending_authority: new_authority,
+    });
+
+    Ok(())
+}
+
+pub fn accept_platform_authority(
+    ctx: Context<AcceptPlatformAuthority>,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+    let previous_authority = platform_config.authority;
+    platform_config.authority = ctx.accounts.new_authority.key();
+    platform_config.pending_authority = None;
+
+    emit_cpi!(PlatformAuthorityAccepted {
+        previous_authority,
+        new_authority: platform_config.authority,
+    });
+
+    Ok(())
+}
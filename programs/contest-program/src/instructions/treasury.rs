@@ -1,10 +1,19 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeAmount;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Account as SplTokenAccount;
+use anchor_spl::token_2022_extensions::transfer_fee::{
+    harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+};
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
-use crate::constants::{TREASURY_SEED, FIRST_PLACE_PERCENTAGE, SECOND_PLACE_PERCENTAGE, THIRD_PLACE_PERCENTAGE};
 use crate::context::*;
 use crate::errors::ContestError;
-use crate::state::{TreasuryAccount};
+use crate::events::*;
+use crate::state::{ContestAccount, ContestStatus, EntryAccount, TreasuryAccount, TREASURY_SEED};
 
 pub fn add_funds_to_prize_pool(
     ctx: Context<AddFundsToPrizePool>,
@@ -14,116 +23,452 @@ pub fn add_funds_to_prize_pool(
     let treasury = &mut ctx.accounts.treasury;
     
     // Transfer funds to treasury
-    let cpi_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: ctx.accounts.contributor.to_account_info(),
-            to: treasury.to_account_info(),
-        },
-    );
-    
-    system_program::transfer(cpi_context, amount)?;
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.contributor_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    let amount = deposit_to_treasury(
+        &ctx.accounts.contributor.to_account_info(),
+        treasury,
+        &ctx.accounts.system_program,
+        fee_token,
+        amount,
+    )?;
     
     // Update treasury and contest
     treasury.total_funds = treasury.total_funds.checked_add(amount).unwrap();
     treasury.prize_pool = treasury.prize_pool.checked_add(amount).unwrap();
     contest.total_prize_pool = contest.total_prize_pool.checked_add(amount).unwrap();
+
+    emit_cpi!(FundsAdded {
+        contest: contest.key(),
+        contributor: ctx.accounts.contributor.key(),
+        amount,
+        prize_pool: treasury.prize_pool,
+    });
     
     Ok(())
 }
 
-pub fn distribute_prizes(
-    ctx: Context<DistributePrizes>,
+pub fn claim_prize(
+    ctx: Context<ClaimPrize>,
 ) -> Result<()> {
     let contest = &ctx.accounts.contest;
     let treasury = &mut ctx.accounts.treasury;
-    
+    let entry = &mut ctx.accounts.entry;
+    let rank = entry.rank.unwrap();
+
     require!(
-        !treasury.is_distributed,
-        ContestError::PrizesAlreadyDistributed
+        Clock::get()?.unix_timestamp < contest.claim_deadline,
+        ContestError::ClaimPeriodEnded
     );
-    
-    // Calculate prize amounts
-    let prize_pool = treasury.prize_pool;
-    
-    let first_place_amount = prize_pool
-        .checked_mul(FIRST_PLACE_PERCENTAGE as u64)
-        .unwrap()
-        .checked_div(100)
-        .unwrap();
-    
-    let second_place_amount = prize_pool
-        .checked_mul(SECOND_PLACE_PERCENTAGE as u64)
-        .unwrap()
-        .checked_div(100)
-        .unwrap();
-    
-    let third_place_amount = prize_pool
-        .checked_mul(THIRD_PLACE_PERCENTAGE as u64)
-        .unwrap()
-        .checked_div(100)
-        .unwrap();
-    
-    // Transfer platform fee
+
+    // The prize pool is locked once the contest closes, so every winner's
+    // share is computed from the same amount regardless of claim order
+    let prize_amount = contest.rules.prize_amount(treasury.prize_pool, rank, entry.tie_count);
+
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.participant_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    pay_from_treasury(
+        treasury,
+        &ctx.accounts.participant.to_account_info(),
+        fee_token,
+        prize_amount,
+    )?;
+
+    entry.prize_claimed = true;
+    treasury.total_funds = treasury.total_funds.checked_sub(prize_amount).unwrap();
+    treasury.prizes_claimed = treasury.prizes_claimed.checked_add(prize_amount).unwrap();
+
+    emit_cpi!(PrizeClaimed {
+        contest: contest.key(),
+        entry: entry.key(),
+        participant: ctx.accounts.participant.key(),
+        rank,
+        amount: prize_amount,
+    });
+
+    Ok(())
+}
+
+pub fn withdraw_platform_fee(
+    ctx: Context<WithdrawPlatformFee>,
+) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
     let platform_fee = treasury.platform_fee;
-    let contest_key = contest.key();
-    let platform_seeds = &[
-        TREASURY_SEED,
-        contest_key.as_ref(),
-        &[treasury.bump],
-    ];
 
-    let seeds_array = &[&platform_seeds[..]];
+    // Anyone can trigger the withdrawal, the fee always goes to the
+    // recipient configured on the platform
+    let fee_token = fee_token_accounts(
+        &ctx.accounts.contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.platform_wallet_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    pay_from_treasury(
+        treasury,
+        &ctx.accounts.platform_wallet.to_account_info(),
+        fee_token,
+        platform_fee,
+    )?;
+
+    treasury.platform_fee_withdrawn = true;
+    treasury.total_funds = treasury.total_funds.checked_sub(platform_fee).unwrap();
+
+    emit_cpi!(PlatformFeeWithdrawn {
+        contest: ctx.accounts.contest.key(),
+        fee_recipient: ctx.accounts.platform_wallet.key(),
+        amount: platform_fee,
+    });
+
+    Ok(())
+}
+
+pub fn claim_entry_refund(
+    ctx: Context<ClaimEntryRefund>,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let treasury = &mut ctx.accounts.treasury;
+    let entry_fee = ctx.accounts.entry.entry_fee;
+
+    require!(
+        Clock::get()?.unix_timestamp < contest.claim_deadline,
+        ContestError::ClaimPeriodEnded
+    );
+
+    // Return the exact entry fee that was paid
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.participant_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    pay_from_treasury(
+        treasury,
+        &ctx.accounts.participant.to_account_info(),
+        fee_token,
+        entry_fee,
+    )?;
+
+    // Reverse the split recorded when the entry was submitted
+    let (platform_fee, prize_amount) = contest.split_fee(entry_fee);
+    treasury.total_funds = treasury.total_funds.checked_sub(entry_fee).unwrap();
+    treasury.platform_fee = treasury.platform_fee.checked_sub(platform_fee).unwrap();
+    treasury.prize_pool = treasury.prize_pool.checked_sub(prize_amount).unwrap();
+    contest.total_prize_pool = contest.total_prize_pool.checked_sub(prize_amount).unwrap();
+
+    emit_cpi!(RefundClaimed {
+        contest: contest.key(),
+        account: ctx.accounts.entry.key(),
+        recipient: ctx.accounts.participant.key(),
+        amount: entry_fee,
+    });
+
+    Ok(())
+}
+
+pub fn claim_vote_refund(
+    ctx: Context<ClaimVoteRefund>,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let treasury = &mut ctx.accounts.treasury;
+    let vote_fee = ctx.accounts.vote.vote_fee;
+
+    // The entry account is pinned to vote.entry by the context. It no longer
+    // belongs to the program once closed, or for an unrevealed commitment.
+    let entry_info = &ctx.accounts.entry;
+    let entry_disqualified = entry_info.owner == ctx.program_id
+        && EntryAccount::try_deserialize(&mut &entry_info.data.borrow()[..])?.is_disqualified();
+
+    require!(
+        contest.status == ContestStatus::Cancelled || entry_disqualified,
+        ContestError::ContestNotCancelled
+    );
+
+    // Votes for a disqualified entry can be reclaimed before the contest ends
+    require!(
+        contest.claim_deadline == 0 || Clock::get()?.unix_timestamp < contest.claim_deadline,
+        ContestError::ClaimPeriodEnded
+    );
+
+    // Return the exact vote fee that was paid
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.voter_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    pay_from_treasury(
+        treasury,
+        &ctx.accounts.voter.to_account_info(),
+        fee_token,
+        vote_fee,
+    )?;
+
+    // Reverse the split recorded when the vote was cast. An unrevealed
+    // commitment still holds its whole fee in the prize pool, and the fee of
+    // a vote for a disqualified entry was taken out of the split already.
+    let (platform_fee, prize_amount) = if entry_disqualified {
+        (0, 0)
+    } else if ctx.accounts.vote.is_unrevealed() {
+        (0, vote_fee)
+    } else {
+        contest.split_fee(vote_fee)
+    };
+    treasury.total_funds = treasury.total_funds.checked_sub(vote_fee).unwrap();
+    treasury.platform_fee = treasury.platform_fee.checked_sub(platform_fee).unwrap();
+    treasury.prize_pool = treasury.prize_pool.checked_sub(prize_amount).unwrap();
+    contest.total_prize_pool = contest.total_prize_pool.checked_sub(prize_amount).unwrap();
+
+    emit_cpi!(RefundClaimed {
+        contest: contest.key(),
+        account: ctx.accounts.vote.key(),
+        recipient: ctx.accounts.voter.key(),
+        amount: vote_fee,
+    });
+
+    Ok(())
+}
+
+// Once the claim period is over, whatever is left in the treasury (unclaimed
+// prizes and refunds, the shares of prize places left unfilled after entries
+// were disqualified during voting, rounding dust and the rent) goes to the fee
+// recipient
+pub fn sweep_treasury(
+    ctx: Context<SweepTreasury>,
+) -> Result<()> {
+    let contest = &ctx.accounts.contest;
+    let clock = Clock::get()?;
+
+    require!(
+        clock.unix_timestamp >= contest.claim_deadline,
+        ContestError::ClaimPeriodNotEnded
+    );
+
+    // The treasury account is closed to the platform wallet when the
+    // instruction exits, a token contest also empties and closes its token account
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.platform_wallet_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    let amount = match fee_token {
+        Some(fee_token) => close_treasury_token_account(
+            &ctx.accounts.treasury,
+            &ctx.accounts.platform_wallet.to_account_info(),
+            fee_token,
+        )?,
+        None => ctx.accounts.treasury.to_account_info().lamports(),
+    };
+
+    emit_cpi!(TreasurySwept {
+        contest: contest.key(),
+        destination: ctx.accounts.platform_wallet.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+// Token accounts used to move funds of a contest denominated in an SPL token.
+// `token_account` is the wallet side of the transfer, the payer or the recipient.
+pub(crate) struct FeeTokenAccounts<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub treasury_token_account: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+// Collects the optional token accounts of an instruction. They are ignored for
+// SOL contests and must all be passed for token contests.
+pub(crate) fn fee_token_accounts<'a, 'info>(
+    contest: &ContestAccount,
+    mint: &'a Option<Box<InterfaceAccount<'info, Mint>>>,
+    token_account: &'a Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    treasury_token_account: &'a mut Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    token_program: &'a Option<Interface<'info, TokenInterface>>,
+) -> Result<Option<FeeTokenAccounts<'a, 'info>>> {
+    if contest.fee_mint.is_none() {
+        return Ok(None);
+    }
+
+    match (mint, token_account, treasury_token_account, token_program) {
+        (Some(mint), Some(token_account), Some(treasury_token_account), Some(token_program)) => {
+            Ok(Some(FeeTokenAccounts {
+                mint,
+                token_account,
+                treasury_token_account,
+                token_program,
+            }))
+        }
+        _ => err!(ContestError::MissingFeeTokenAccounts),
+    }
+}
 
-    let platform_fee_cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: treasury.to_account_info(),
-            to: ctx.accounts.platform_wallet.to_account_info(),
+// Moves a payment into the treasury and returns the amount it actually
+// received. Token-2022 transfer fees are withheld from what arrives, so the
+// received amount is measured instead of assumed.
+pub(crate) fn deposit_to_treasury<'info>(
+    payer: &AccountInfo<'info>,
+    treasury: &Account<'info, TreasuryAccount>,
+    system_program: &Program<'info, System>,
+    fee_token: Option<FeeTokenAccounts<'_, 'info>>,
+    amount: u64,
+) -> Result<u64> {
+    let Some(fee_token) = fee_token else {
+        let cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: treasury.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        return Ok(amount);
+    };
+
+    let balance_before = fee_token.treasury_token_account.amount;
+
+    let cpi_context = CpiContext::new(
+        fee_token.token_program.to_account_info(),
+        TransferChecked {
+            from: fee_token.token_account.to_account_info(),
+            mint: fee_token.mint.to_account_info(),
+            to: fee_token.treasury_token_account.to_account_info(),
+            authority: payer.clone(),
         },
-        seeds_array
     );
-    
-    system_program::transfer(platform_fee_cpi_context, platform_fee)?;
-    
-    // Transfer first place prize
-    let first_place_cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: treasury.to_account_info(),
-            to: ctx.accounts.first_place_recipient.to_account_info(),
+    token_interface::transfer_checked(cpi_context, amount, fee_token.mint.decimals)?;
+
+    fee_token.treasury_token_account.reload()?;
+    Ok(fee_token
+        .treasury_token_account
+        .amount
+        .checked_sub(balance_before)
+        .unwrap())
+}
+
+// Pays out of the treasury, in lamports or in the contest's token
+pub(crate) fn pay_from_treasury<'info>(
+    treasury: &Account<'info, TreasuryAccount>,
+    recipient: &AccountInfo<'info>,
+    fee_token: Option<FeeTokenAccounts<'_, 'info>>,
+    amount: u64,
+) -> Result<()> {
+    let Some(fee_token) = fee_token else {
+        return transfer_from_treasury(&treasury.to_account_info(), recipient, amount);
+    };
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        TREASURY_SEED,
+        treasury.contest.as_ref(),
+        &[treasury.bump],
+    ]];
+    let cpi_context = CpiContext::new_with_signer(
+        fee_token.token_program.to_account_info(),
+        TransferChecked {
+            from: fee_token.treasury_token_account.to_account_info(),
+            mint: fee_token.mint.to_account_info(),
+            to: fee_token.token_account.to_account_info(),
+            authority: treasury.to_account_info(),
         },
-        seeds_array
+        signer_seeds,
     );
-    
-    system_program::transfer(first_place_cpi_context, first_place_amount)?;
-    
-    // Transfer second place prize
-    let second_place_cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: treasury.to_account_info(),
-            to: ctx.accounts.second_place_recipient.to_account_info(),
+
+    token_interface::transfer_checked(cpi_context, amount, fee_token.mint.decimals)
+}
+
+// Sends the remaining token balance to the recipient's token account, then
+// closes the treasury token account and returns the rent to the recipient.
+// Returns the amount of tokens swept.
+fn close_treasury_token_account<'info>(
+    treasury: &Account<'info, TreasuryAccount>,
+    recipient: &AccountInfo<'info>,
+    fee_token: FeeTokenAccounts<'_, 'info>,
+) -> Result<u64> {
+    let remaining = fee_token.treasury_token_account.amount;
+    let treasury_token_account = fee_token.treasury_token_account.to_account_info();
+    let mint = fee_token.mint.to_account_info();
+    let token_program = fee_token.token_program.to_account_info();
+
+    pay_from_treasury(treasury, recipient, Some(fee_token), remaining)?;
+
+    harvest_withheld_transfer_fees(&treasury_token_account, &mint, &token_program)?;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        TREASURY_SEED,
+        treasury.contest.as_ref(),
+        &[treasury.bump],
+    ]];
+    let cpi_context = CpiContext::new_with_signer(
+        token_program,
+        CloseAccount {
+            account: treasury_token_account,
+            destination: recipient.clone(),
+            authority: treasury.to_account_info(),
         },
-        seeds_array
+        signer_seeds,
     );
-    
-    system_program::transfer(second_place_cpi_context, second_place_amount)?;
-    
-    // Transfer third place prize
-    let third_place_cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: treasury.to_account_info(),
-            to: ctx.accounts.third_place_recipient.to_account_info(),
+    token_interface::close_account(cpi_context)?;
+
+    Ok(remaining)
+}
+
+// Token-2022 refuses to close an account holding withheld transfer fees,
+// so they are moved to the mint before the account is closed
+pub(crate) fn harvest_withheld_transfer_fees<'info>(
+    token_account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if withheld_transfer_fees(token_account)? == 0 {
+        return Ok(());
+    }
+
+    let cpi_context = CpiContext::new(
+        token_program.clone(),
+        HarvestWithheldTokensToMint {
+            token_program_id: token_program.clone(),
+            mint: mint.clone(),
         },
-        seeds_array
     );
-    
-    system_program::transfer(third_place_cpi_context, third_place_amount)?;
-    
-    // Mark treasury as distributed
-    treasury.is_distributed = true;
-    
+    harvest_withheld_tokens_to_mint(cpi_context, vec![token_account.clone()])
+}
+
+// Transfer fees withheld on a Token-2022 account, zero for SPL Token accounts
+fn withheld_transfer_fees(token_account: &AccountInfo) -> Result<u64> {
+    let data = token_account.try_borrow_data()?;
+    let state = StateWithExtensions::<SplTokenAccount>::unpack(&data)?;
+
+    Ok(state
+        .get_extension::<TransferFeeAmount>()
+        .map(|transfer_fee| u64::from(transfer_fee.withheld_amount))
+        .unwrap_or(0))
+}
+
+// The treasury is a program-owned account holding data, so it cannot be the
+// source of a system program transfer. Lamports are moved directly instead.
+fn transfer_from_treasury<'info>(
+    treasury: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    treasury.sub_lamports(amount)?;
+    recipient.add_lamports(amount)?;
+
     Ok(())
-} 
\ No newline at end of file
+}
@@ -1,10 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
+use crate::constants::{BASIS_POINTS, MAX_JUDGE_SCORE};
 use crate::context::*;
 use crate::errors::ContestError;
+use crate::events::*;
 use crate::state::*;
-use crate::constants::PLATFORM_FEE_PERCENTAGE;
+
+use super::entry::load_entry;
+use super::treasury::{deposit_to_treasury, fee_token_accounts, harvest_withheld_transfer_fees};
 
 pub fn cast_vote(
     ctx: Context<CastVote>,
@@ -12,35 +18,81 @@ pub fn cast_vote(
 ) -> Result<()> {
     let contest = &ctx.accounts.contest;
     let treasury = &ctx.accounts.treasury;
-    
-    // Transfer vote fee to treasury
-    let vote_fee = contest.vote_fee.checked_mul(token_amount).unwrap();
-    
-    let cpi_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: ctx.accounts.voter.to_account_info(),
-            to: treasury.to_account_info(),
-        },
+    let clock = Clock::get()?;
+
+    // Secret ballot contests only accept votes through commit_vote
+    require!(
+        !contest.rules.is_secret_ballot(),
+        ContestError::InvalidVotingMethod
     );
-    
-    system_program::transfer(cpi_context, vote_fee)?;
+    // A zero-amount vote would be free yet still count as a voter
+    require!(token_amount > 0, ContestError::InvalidVoteAmount);
+
+    record_voter(
+        &mut ctx.accounts.voter_record,
+        contest,
+        ctx.accounts.voter.key(),
+        ctx.bumps.voter_record,
+        clock.unix_timestamp,
+    )?;
+
+    // The weight of the vote and the fee it pays depend on the voting mode.
+    // Quadratic votes cost the square of the votes bought for this entry,
+    // so the weight grows with the square root of what was paid.
+    let voting_mode = &contest.rules.voting_mode;
+    let vote_cost = voting_mode
+        .vote_cost(contest.vote_fee, token_amount)
+        .ok_or(ContestError::VoteAmountTooLarge)?;
+
+    let (weight, locked_amount) = match voting_mode {
+        VotingMode::Linear | VotingMode::Quadratic => (token_amount, 0),
+        VotingMode::TokenLock { .. } => {
+            let locked_amount = lock_vote_tokens(
+                &ctx.accounts.voter,
+                &ctx.accounts.governance_mint,
+                &ctx.accounts.voter_governance_token_account,
+                &mut ctx.accounts.vote_escrow,
+                &ctx.accounts.governance_token_program,
+                token_amount,
+            )?;
+            (locked_amount, locked_amount)
+        }
+    };
+
+    // Transfer vote fee to treasury, recording what the treasury received
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.voter_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    let vote_fee = deposit_to_treasury(
+        &ctx.accounts.voter.to_account_info(),
+        treasury,
+        &ctx.accounts.system_program,
+        fee_token,
+        vote_cost,
+    )?;
 
     // Create vote record
     let vote = &mut ctx.accounts.vote;
-    let clock = Clock::get()?;
     
     vote.voter = ctx.accounts.voter.key();
     vote.contest = contest.key();
     vote.entry = ctx.accounts.entry.key();
     vote.timestamp = clock.unix_timestamp;
     vote.token_amount = token_amount;
+    vote.vote_fee = vote_fee;
+    vote.locked_amount = locked_amount;
+    vote.commitment = None;
+    vote.revealed = false;
     vote.bump = ctx.bumps.vote;
 
     // Update entry's vote count
     let entry = &mut ctx.accounts.entry;
     entry.vote_count = entry.vote_count.checked_add(1).unwrap();
-    entry.weighted_votes = entry.weighted_votes.checked_add(token_amount).unwrap();
+    entry.weighted_votes = entry.weighted_votes.checked_add(weight).unwrap();
 
     // Update contest's vote count
     let contest = &mut ctx.accounts.contest;
@@ -51,29 +103,461 @@ pub fn cast_vote(
     treasury.total_funds = treasury.total_funds.checked_add(vote_fee).unwrap();
     
     // Split between platform fee and prize pool
-    let platform_fee = vote_fee.checked_mul(PLATFORM_FEE_PERCENTAGE as u64).unwrap().checked_div(100).unwrap();
-    let prize_amount = vote_fee.checked_sub(platform_fee).unwrap();
+    let (platform_fee, prize_amount) = contest.split_fee(vote_fee);
     
     treasury.platform_fee = treasury.platform_fee.checked_add(platform_fee).unwrap();
     treasury.prize_pool = treasury.prize_pool.checked_add(prize_amount).unwrap();
+    entry.record_vote_fee(vote_fee, platform_fee);
     
     // Update contest's total prize pool
     contest.total_prize_pool = contest.total_prize_pool.checked_add(prize_amount).unwrap();
 
+    emit_cpi!(VoteCast {
+        contest: contest.key(),
+        entry: entry.key(),
+        voter: ctx.accounts.voter.key(),
+        token_amount,
+        vote_fee,
+        weighted_votes: entry.weighted_votes,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
 
-pub fn count_votes(
-    ctx: Context<CountVotes>,
+// Records a sealed vote: only a hash of the entry, the amount and a salt is
+// stored until the voter reveals it after the voting window
+pub fn commit_vote(
+    ctx: Context<CommitVote>,
+    commitment: [u8; 32],
+    token_amount: u64,
 ) -> Result<()> {
+    let contest = &ctx.accounts.contest;
+    let treasury = &ctx.accounts.treasury;
+    let clock = Clock::get()?;
+
+    require!(
+        contest.rules.is_secret_ballot(),
+        ContestError::InvalidVotingMethod
+    );
+    // A zero-amount vote would be free yet still count as a voter
+    require!(token_amount > 0, ContestError::InvalidVoteAmount);
+
+    record_voter(
+        &mut ctx.accounts.voter_record,
+        contest,
+        ctx.accounts.voter.key(),
+        ctx.bumps.voter_record,
+        clock.unix_timestamp,
+    )?;
+
+    // The fee and any token lock are paid up front, so revealing can't
+    // change what the vote costs
+    let voting_mode = &contest.rules.voting_mode;
+    let vote_cost = voting_mode
+        .vote_cost(contest.vote_fee, token_amount)
+        .ok_or(ContestError::VoteAmountTooLarge)?;
+
+    let locked_amount = match voting_mode {
+        VotingMode::Linear | VotingMode::Quadratic => 0,
+        VotingMode::TokenLock { .. } => lock_vote_tokens(
+            &ctx.accounts.voter,
+            &ctx.accounts.governance_mint,
+            &ctx.accounts.voter_governance_token_account,
+            &mut ctx.accounts.vote_escrow,
+            &ctx.accounts.governance_token_program,
+            token_amount,
+        )?,
+    };
+
+    let fee_token = fee_token_accounts(
+        contest,
+        &ctx.accounts.fee_mint,
+        &ctx.accounts.voter_token_account,
+        &mut ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+    )?;
+    let vote_fee = deposit_to_treasury(
+        &ctx.accounts.voter.to_account_info(),
+        treasury,
+        &ctx.accounts.system_program,
+        fee_token,
+        vote_cost,
+    )?;
+
+    // The entry stays unknown until the vote is revealed
+    let vote = &mut ctx.accounts.vote;
+
+    vote.voter = ctx.accounts.voter.key();
+    vote.contest = contest.key();
+    vote.entry = Pubkey::default();
+    vote.timestamp = clock.unix_timestamp;
+    vote.token_amount = token_amount;
+    vote.vote_fee = vote_fee;
+    vote.locked_amount = locked_amount;
+    vote.commitment = Some(commitment);
+    vote.revealed = false;
+    vote.bump = ctx.bumps.vote;
+
     let contest = &mut ctx.accounts.contest;
-    
-    // This is a simplified implementation
-    // In a real system, this would involve fetching all entries,
-    // sorting them by weighted votes, and assigning ranks
-    
-    // For now, we just mark that winners have been decided
+    let treasury = &mut ctx.accounts.treasury;
+
+    // The whole fee sits in the prize pool until the vote is revealed, so a
+    // commitment that is never revealed forfeits it to the winners
+    treasury.total_funds = treasury.total_funds.checked_add(vote_fee).unwrap();
+    treasury.prize_pool = treasury.prize_pool.checked_add(vote_fee).unwrap();
+    contest.total_prize_pool = contest.total_prize_pool.checked_add(vote_fee).unwrap();
+
+    emit_cpi!(VoteCommitted {
+        contest: contest.key(),
+        vote: vote.key(),
+        voter: ctx.accounts.voter.key(),
+        vote_fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Opens a committed vote and counts it for the entry it was cast for
+pub fn reveal_vote(
+    ctx: Context<RevealVote>,
+    salt: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let contest = &mut ctx.accounts.contest;
+    let entry = &mut ctx.accounts.entry;
+    let vote = &mut ctx.accounts.vote;
+
+    require!(
+        clock.unix_timestamp < contest.reveal_end_time(),
+        ContestError::RevealPeriodEnded
+    );
+
+    require!(
+        vote.commitment
+            == Some(vote_commitment(
+                &ctx.accounts.voter.key(),
+                &contest.key(),
+                &entry.key(),
+                vote.token_amount,
+                &salt,
+            )),
+        ContestError::InvalidCommitment
+    );
+
+    vote.entry = entry.key();
+    vote.revealed = true;
+
+    let treasury = &mut ctx.accounts.treasury;
+
+    if entry.is_disqualified() {
+        // A vote revealed for a disqualified entry isn't counted, and its whole
+        // fee leaves the prize pool so the voter can claim it back
+        treasury.prize_pool = treasury.prize_pool.checked_sub(vote.vote_fee).unwrap();
+        contest.total_prize_pool = contest.total_prize_pool.checked_sub(vote.vote_fee).unwrap();
+    } else {
+        let weight = match contest.rules.voting_mode {
+            VotingMode::Linear | VotingMode::Quadratic => vote.token_amount,
+            VotingMode::TokenLock { .. } => vote.locked_amount,
+        };
+
+        entry.vote_count = entry.vote_count.checked_add(1).unwrap();
+        entry.weighted_votes = entry.weighted_votes.checked_add(weight).unwrap();
+        contest.vote_count = contest.vote_count.checked_add(1).unwrap();
+
+        // Now that the vote counts, move the platform's share out of the prize pool
+        let (platform_fee, _) = contest.split_fee(vote.vote_fee);
+
+        treasury.prize_pool = treasury.prize_pool.checked_sub(platform_fee).unwrap();
+        treasury.platform_fee = treasury.platform_fee.checked_add(platform_fee).unwrap();
+        contest.total_prize_pool = contest.total_prize_pool.checked_sub(platform_fee).unwrap();
+        entry.record_vote_fee(vote.vote_fee, platform_fee);
+    }
+
+    emit_cpi!(VoteRevealed {
+        contest: contest.key(),
+        vote: vote.key(),
+        entry: entry.key(),
+        voter: ctx.accounts.voter.key(),
+        token_amount: vote.token_amount,
+        weighted_votes: entry.weighted_votes,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Returns the governance tokens locked by a vote once the contest is over
+pub fn unlock_vote_tokens(
+    ctx: Context<UnlockVoteTokens>,
+) -> Result<()> {
+    let vote = &mut ctx.accounts.vote;
+    let vote_key = vote.key();
+    let amount = ctx.accounts.vote_escrow.amount;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        VOTE_ESCROW_SEED,
+        vote_key.as_ref(),
+        &[ctx.bumps.vote_escrow],
+    ]];
+    let escrow_info = ctx.accounts.vote_escrow.to_account_info();
+    let mint_info = ctx.accounts.governance_mint.to_account_info();
+    let token_program_info = ctx.accounts.governance_token_program.to_account_info();
+
+    let cpi_context = CpiContext::new_with_signer(
+        token_program_info.clone(),
+        TransferChecked {
+            from: escrow_info.clone(),
+            mint: mint_info.clone(),
+            to: ctx.accounts.voter_governance_token_account.to_account_info(),
+            authority: escrow_info.clone(),
+        },
+        signer_seeds,
+    );
+    token_interface::transfer_checked(cpi_context, amount, ctx.accounts.governance_mint.decimals)?;
+
+    // Close the escrow and return its rent to the voter
+    harvest_withheld_transfer_fees(&escrow_info, &mint_info, &token_program_info)?;
+
+    let cpi_context = CpiContext::new_with_signer(
+        token_program_info,
+        CloseAccount {
+            account: escrow_info.clone(),
+            destination: ctx.accounts.voter.to_account_info(),
+            authority: escrow_info,
+        },
+        signer_seeds,
+    );
+    token_interface::close_account(cpi_context)?;
+
+    vote.locked_amount = 0;
+
+    emit_cpi!(VoteTokensUnlocked {
+        contest: ctx.accounts.contest.key(),
+        vote: vote_key,
+        voter: ctx.accounts.voter.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+// Records a judge's score for an entry, replacing any earlier score they gave it
+pub fn submit_judge_score(
+    ctx: Context<SubmitJudgeScore>,
+    score: u8,
+) -> Result<()> {
+    require!(score <= MAX_JUDGE_SCORE, ContestError::InvalidJudgeScore);
+
+    let entry = &mut ctx.accounts.entry;
+    let judge_score = &mut ctx.accounts.judge_score;
+
+    let previous_score = if judge_score.entry == Pubkey::default() {
+        judge_score.contest = ctx.accounts.contest.key();
+        judge_score.entry = entry.key();
+        judge_score.judge = ctx.accounts.judge.key();
+        judge_score.bump = ctx.bumps.judge_score;
+        0
+    } else {
+        judge_score.score
+    };
+    judge_score.score = score;
+
+    entry.judge_score = entry
+        .judge_score
+        .checked_sub(previous_score as u64)
+        .unwrap()
+        .checked_add(score as u64)
+        .unwrap();
+
+    emit_cpi!(JudgeScoreSubmitted {
+        contest: ctx.accounts.contest.key(),
+        entry: entry.key(),
+        judge: ctx.accounts.judge.key(),
+        score,
+        judge_score: entry.judge_score,
+    });
+
+    Ok(())
+}
+
+pub fn count_votes<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CountVotes<'info>>,
+) -> Result<()> {
+    let contest = &mut ctx.accounts.contest;
+    let contest_key = contest.key();
+    let clock = Clock::get()?;
+
+    // Anyone can rank the entries once the creator has had time to do it
+    require!(
+        contest.can_finalize(&ctx.accounts.authority.key(), clock.unix_timestamp),
+        ContestError::Unauthorized
+    );
+
+    // Every entry of the contest must be passed in remaining_accounts
+    require!(
+        ctx.remaining_accounts.len() == contest.entry_count as usize,
+        ContestError::EntryCountMismatch
+    );
+
+    let mut entries: Vec<Account<'info, EntryAccount>> = Vec::with_capacity(ctx.remaining_accounts.len());
+    for account_info in ctx.remaining_accounts.iter() {
+        require!(account_info.is_writable, ContestError::InvalidEntryAccount);
+
+        let entry = load_entry(account_info, &contest_key)?;
+
+        require!(
+            !entries.iter().any(|e| e.key() == account_info.key()),
+            ContestError::DuplicateEntry
+        );
+
+        entries.push(entry);
+    }
+
+    // Disqualified entries are passed in but never ranked
+    entries.retain(|entry| !entry.is_disqualified());
+
+    // Sort entries by their blend of judge scores and public votes, highest
+    // first. Equal scores are ordered by the contest's tie-break policy and
+    // finally by address so the ranking is always deterministic.
+    // The totals are summed as u128 so many large entries cannot overflow them
+    let total_judge_score: u128 = entries.iter().map(|entry| entry.judge_score as u128).sum();
+    let total_weighted_votes: u128 = entries.iter().map(|entry| entry.weighted_votes as u128).sum();
+    let judge_weight_bps = contest.rules.judge_weight_bps;
+    let tie_break = contest.rules.tie_break.clone();
+
+    let mut ranked: Vec<(u128, Account<'info, EntryAccount>)> = entries
+        .into_iter()
+        .map(|entry| {
+            let score = ranking_score(&entry, judge_weight_bps, total_judge_score, total_weighted_votes);
+            (score, entry)
+        })
+        .collect();
+    ranked.sort_by(|(score_a, a), (score_b, b)| {
+        score_b
+            .cmp(score_a)
+            .then_with(|| tie_break.compare(a, b))
+            .then_with(|| a.key().cmp(&b.key()))
+    });
+
+    // Assign ranks to the top entries and persist them. Under SplitPrize,
+    // entries with equal scores share the rank of the first of them.
+    let prize_places = contest.rules.prize_places();
+    let mut winners = Vec::with_capacity(prize_places);
+    let mut index = 0;
+    while index < ranked.len() && index < prize_places {
+        let score = ranked[index].0;
+        let tie_count = if tie_break.splits_prize() {
+            ranked[index..].iter().take_while(|(other, _)| *other == score).count()
+        } else {
+            1
+        };
+
+        for (_, entry) in ranked[index..index + tie_count].iter_mut() {
+            entry.rank = Some(index as u8 + 1);
+            entry.tie_count = tie_count as u32;
+            entry.exit(ctx.program_id)?;
+            winners.push(entry.key());
+        }
+
+        index += tie_count;
+    }
+
     contest.winners_decided = true;
-    
+
+    emit_cpi!(VotesCounted {
+        contest: contest_key,
+        caller: ctx.accounts.authority.key(),
+        winners,
+    });
+
     Ok(())
-} 
\ No newline at end of file
+}
+
+// Blends an entry's share of all judge scores with its share of all public
+// votes. Both shares are scaled to the common denominator
+// total_judge_score * total_weighted_votes so entries compare exactly.
+fn ranking_score(
+    entry: &EntryAccount,
+    judge_weight_bps: u16,
+    total_judge_score: u128,
+    total_weighted_votes: u128,
+) -> u128 {
+    let judge_weight = judge_weight_bps as u128;
+    let public_weight = BASIS_POINTS as u128 - judge_weight;
+
+    let judge_part = (judge_weight * entry.judge_score as u128)
+        .checked_mul(total_weighted_votes.max(1))
+        .unwrap();
+    let public_part = (public_weight * entry.weighted_votes as u128)
+        .checked_mul(total_judge_score.max(1))
+        .unwrap();
+
+    judge_part.checked_add(public_part).unwrap()
+}
+
+// Checks the voting window and the voter's vote limit, then counts the vote
+// against the voter's record
+fn record_voter(
+    voter_record: &mut Account<VoterRecord>,
+    contest: &Account<ContestAccount>,
+    voter: Pubkey,
+    bump: u8,
+    current_time: i64,
+) -> Result<()> {
+    // Votes are only accepted until the voting window closes
+    require!(
+        current_time < contest.voting_end_time,
+        ContestError::VotingPeriodEnded
+    );
+
+    // Each voter can vote up to the contest's limit
+    require!(
+        voter_record.votes_cast < contest.rules.max_votes_per_voter,
+        ContestError::AlreadyVoted
+    );
+
+    if voter_record.votes_cast == 0 {
+        voter_record.contest = contest.key();
+        voter_record.voter = voter;
+        voter_record.bump = bump;
+    }
+    voter_record.votes_cast = voter_record.votes_cast.checked_add(1).unwrap();
+
+    Ok(())
+}
+
+// Moves the voter's governance tokens into the vote's escrow and returns the
+// amount that arrived, which becomes the weight of the vote
+fn lock_vote_tokens<'info>(
+    voter: &Signer<'info>,
+    governance_mint: &Option<Box<InterfaceAccount<'info, Mint>>>,
+    voter_token_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    vote_escrow: &mut Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+    amount: u64,
+) -> Result<u64> {
+    let (Some(governance_mint), Some(voter_token_account), Some(vote_escrow), Some(token_program)) =
+        (governance_mint, voter_token_account, vote_escrow, token_program)
+    else {
+        return err!(ContestError::MissingGovernanceTokenAccounts);
+    };
+
+    require!(amount > 0, ContestError::InvalidLockAmount);
+
+    let cpi_context = CpiContext::new(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: voter_token_account.to_account_info(),
+            mint: governance_mint.to_account_info(),
+            to: vote_escrow.to_account_info(),
+            authority: voter.to_account_info(),
+        },
+    );
+    token_interface::transfer_checked(cpi_context, amount, governance_mint.decimals)?;
+
+    // The escrow was created by this instruction, so its balance is what arrived
+    vote_escrow.reload()?;
+    Ok(vote_escrow.amount)
+}
@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 pub mod constants;
 pub mod context;
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
@@ -16,17 +17,21 @@ pub mod contest_program {
     use super::*;
 
     // コンテスト管理
+    #[allow(clippy::too_many_arguments)]
     pub fn create_contest(
         ctx: Context<CreateContest>,
         contest_id: [u8; 32],
         title: String,
         description: String,
         start_time: i64,
-        end_time: i64,
+        entry_end_time: i64,
+        voting_end_time: i64,
         entry_fee: u64,
         vote_fee: u64,
+        rules: state::ContestRules,
+        judges: Vec<Pubkey>,
     ) -> Result<()> {
-        contest::create_contest(ctx, contest_id, title, description, start_time, end_time, entry_fee, vote_fee)
+        contest::create_contest(ctx, contest_id, title, description, start_time, entry_end_time, voting_end_time, entry_fee, vote_fee, rules, judges)
     }
 
     pub fn update_contest(
@@ -34,9 +39,10 @@ pub mod contest_program {
         title: Option<String>,
         description: Option<String>,
         start_time: Option<i64>,
-        end_time: Option<i64>,
+        entry_end_time: Option<i64>,
+        voting_end_time: Option<i64>,
     ) -> Result<()> {
-        contest::update_contest(ctx, title, description, start_time, end_time)
+        contest::update_contest(ctx, title, description, start_time, entry_end_time, voting_end_time)
     }
 
     pub fn update_contest_status(
@@ -46,19 +52,46 @@ pub mod contest_program {
         contest::update_contest_status(ctx, new_status)
     }
 
+    pub fn advance_contest(
+        ctx: Context<AdvanceContest>,
+    ) -> Result<()> {
+        contest::advance_contest(ctx)
+    }
+
+    pub fn cancel_contest(
+        ctx: Context<CancelContest>,
+    ) -> Result<()> {
+        contest::cancel_contest(ctx)
+    }
+
     // エントリー管理
     pub fn submit_entry(
         ctx: Context<SubmitEntry>,
         content_uri: String,
+        content_hash: [u8; 32],
     ) -> Result<()> {
-        entry::submit_entry(ctx, content_uri)
+        entry::submit_entry(ctx, content_uri, content_hash)
     }
 
     pub fn update_entry(
         ctx: Context<UpdateEntry>,
         content_uri: String,
+        content_hash: [u8; 32],
     ) -> Result<()> {
-        entry::update_entry(ctx, content_uri)
+        entry::update_entry(ctx, content_uri, content_hash)
+    }
+
+    pub fn withdraw_entry(
+        ctx: Context<WithdrawEntry>,
+    ) -> Result<()> {
+        entry::withdraw_entry(ctx)
+    }
+
+    pub fn disqualify_entry(
+        ctx: Context<DisqualifyEntry>,
+        reason: u8,
+    ) -> Result<()> {
+        entry::disqualify_entry(ctx, reason)
     }
 
     // 投票システム
@@ -69,8 +102,36 @@ pub mod contest_program {
         voting::cast_vote(ctx, token_amount)
     }
 
-    pub fn count_votes(
-        ctx: Context<CountVotes>,
+    pub fn commit_vote(
+        ctx: Context<CommitVote>,
+        commitment: [u8; 32],
+        token_amount: u64,
+    ) -> Result<()> {
+        voting::commit_vote(ctx, commitment, token_amount)
+    }
+
+    pub fn reveal_vote(
+        ctx: Context<RevealVote>,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        voting::reveal_vote(ctx, salt)
+    }
+
+    pub fn unlock_vote_tokens(
+        ctx: Context<UnlockVoteTokens>,
+    ) -> Result<()> {
+        voting::unlock_vote_tokens(ctx)
+    }
+
+    pub fn submit_judge_score(
+        ctx: Context<SubmitJudgeScore>,
+        score: u8,
+    ) -> Result<()> {
+        voting::submit_judge_score(ctx, score)
+    }
+
+    pub fn count_votes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CountVotes<'info>>,
     ) -> Result<()> {
         voting::count_votes(ctx)
     }
@@ -83,10 +144,34 @@ pub mod contest_program {
         treasury::add_funds_to_prize_pool(ctx, amount)
     }
 
-    pub fn distribute_prizes(
-        ctx: Context<DistributePrizes>,
+    pub fn claim_prize(
+        ctx: Context<ClaimPrize>,
+    ) -> Result<()> {
+        treasury::claim_prize(ctx)
+    }
+
+    pub fn withdraw_platform_fee(
+        ctx: Context<WithdrawPlatformFee>,
     ) -> Result<()> {
-        treasury::distribute_prizes(ctx)
+        treasury::withdraw_platform_fee(ctx)
+    }
+
+    pub fn sweep_treasury(
+        ctx: Context<SweepTreasury>,
+    ) -> Result<()> {
+        treasury::sweep_treasury(ctx)
+    }
+
+    pub fn claim_entry_refund(
+        ctx: Context<ClaimEntryRefund>,
+    ) -> Result<()> {
+        treasury::claim_entry_refund(ctx)
+    }
+
+    pub fn claim_vote_refund(
+        ctx: Context<ClaimVoteRefund>,
+    ) -> Result<()> {
+        treasury::claim_vote_refund(ctx)
     }
 
     // プラットフォーム設定
@@ -95,7 +180,46 @@ pub mod contest_program {
         platform_fee_percentage: u8,
         min_entry_fee: u64,
         min_vote_fee: u64,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        contest::initialize_platform(ctx, platform_fee_percentage, min_entry_fee, min_vote_fee, fee_recipient)
+    }
+
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        platform_fee_percentage: Option<u8>,
+        min_entry_fee: Option<u64>,
+        min_vote_fee: Option<u64>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        platform::update_platform_config(ctx, platform_fee_percentage, min_entry_fee, min_vote_fee, fee_recipient)
+    }
+
+    pub fn migrate_platform_config(
+        ctx: Context<MigratePlatformConfig>,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        platform::migrate_platform_config(ctx, fee_recipient)
+    }
+
+    pub fn set_platform_pause(
+        ctx: Context<SetPlatformPause>,
+        paused: bool,
+        paused_instructions: u32,
+    ) -> Result<()> {
+        platform::set_platform_pause(ctx, paused, paused_instructions)
+    }
+
+    pub fn propose_platform_authority(
+        ctx: Context<ProposePlatformAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        platform::propose_platform_authority(ctx, new_authority)
+    }
+
+    pub fn accept_platform_authority(
+        ctx: Context<AcceptPlatformAuthority>,
     ) -> Result<()> {
-        contest::initialize_platform(ctx, platform_fee_percentage, min_entry_fee, min_vote_fee)
+        platform::accept_platform_authority(ctx)
     }
 } 
\ No newline at end of file
@@ -1,4 +1,10 @@
 use anchor_lang::prelude::*;
+use std::cmp::Ordering;
+
+use crate::constants::{
+    BASIS_POINTS, CLAIM_PERIOD, FINALIZATION_TIMEOUT, MAX_ENTRIES_PER_PARTICIPANT,
+    MAX_ENTRY_REVISIONS, MAX_JUDGES, MAX_PRIZE_PLACES,
+};
 
 // アカウントサイズの計算用定数
 pub const MAX_TITLE_LENGTH: usize = 100;
@@ -10,11 +16,10 @@ pub const CONTEST_SEED: &[u8] = b"contest";
 pub const ENTRY_SEED: &[u8] = b"entry";
 pub const VOTE_SEED: &[u8] = b"vote";
 pub const TREASURY_SEED: &[u8] = b"treasury";
-
-// 賞金分配率
-pub const FIRST_PLACE_PERCENTAGE: u8 = 50;
-pub const SECOND_PLACE_PERCENTAGE: u8 = 30;
-pub const THIRD_PLACE_PERCENTAGE: u8 = 20;
+pub const VOTE_ESCROW_SEED: &[u8] = b"vote-escrow";
+pub const JUDGE_SCORE_SEED: &[u8] = b"judge-score";
+pub const PARTICIPANT_SEED: &[u8] = b"participant";
+pub const ENTRY_REVISIONS_SEED: &[u8] = b"entry-revisions";
 
 #[account]
 pub struct PlatformConfig {
@@ -23,10 +28,20 @@ pub struct PlatformConfig {
     pub min_entry_fee: u64,             // 最小エントリー料金（lamports）
     pub min_vote_fee: u64,              // 最小投票料金（lamports）
     pub bump: u8,                       // PDA bump seed
+    pub pending_authority: Option<Pubkey>, // 権限移譲の提案先（承認待ち）
+    pub fee_recipient: Pubkey,          // プラットフォーム手数料の受取アドレス
+    pub paused: bool,                   // 緊急停止中かどうか（返金以外の全ての変更系命令を停止）
+    pub paused_instructions: u32,       // 個別に停止している命令グループのビットマスク（PAUSE_*）
 }
 
+// フィールドは末尾にのみ追加する（migrate_platform_configでゼロ初期化された領域が新フィールドになる）
 impl PlatformConfig {
-    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1;
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1 + (1 + 32) + 32 + 1 + 4;
+
+    // 指定した命令グループ（PAUSE_*）が停止中かどうか
+    pub fn is_paused(&self, instructions: u32) -> bool {
+        self.paused || self.paused_instructions & instructions != 0
+    }
 }
 
 #[account]
@@ -36,20 +51,190 @@ pub struct ContestAccount {
     pub title: String,                  // コンテストのタイトル
     pub description: String,            // コンテストの説明
     pub start_time: i64,                // 開始タイムスタンプ
-    pub end_time: i64,                  // 終了タイムスタンプ
-    pub entry_fee: u64,                 // エントリー料金（lamports）
-    pub vote_fee: u64,                  // 投票料金（lamports）
+    pub entry_end_time: i64,            // エントリー締切タイムスタンプ
+    pub voting_end_time: i64,           // 投票締切タイムスタンプ
+    pub entry_fee: u64,                 // エントリー料金（lamportsまたはfee_mintの最小単位）
+    pub vote_fee: u64,                  // 投票料金（lamportsまたはfee_mintの最小単位）
+    pub fee_mint: Option<Pubkey>,       // 料金・賞金のトークンのMint（NoneはSOL建て）
+    pub platform_fee_percentage: u8,    // 作成時点のプラットフォーム手数料割合（0-100）
+    pub rules: ContestRules,            // 作成者が設定したコンテストのルール
     pub status: ContestStatus,          // コンテストの現在のステータス
     pub treasury: Pubkey,               // 賞金プール用トレジャリーアカウント
     pub entry_count: u32,               // 現在のエントリー数
     pub vote_count: u32,                // 現在の投票数
     pub total_prize_pool: u64,          // 総賞金プール（lamports）
     pub winners_decided: bool,          // 勝者が決定されたかどうか
+    pub claim_deadline: i64,            // 賞金・返金の受取期限（確定または中止時に設定、0は未設定）
+    pub judges: Vec<Pubkey>,            // 審査員のアドレス（空の場合は一般投票のみ）
+    pub next_entry_index: u32,          // 次に提出されるエントリーの番号（エントリーPDAのシード）
+    pub disqualified_entry_count: u32,  // 失格になったエントリー数（entry_countに含まれる）
     pub bump: u8,                       // PDA bump seed
 }
 
 impl ContestAccount {
-    pub const SIZE: usize = 32 + 32 + MAX_TITLE_LENGTH + MAX_DESCRIPTION_LENGTH + 8 + 8 + 8 + 8 + 1 + 32 + 4 + 4 + 8 + 1 + 1;
+    pub const SIZE: usize = 32 + 32 + MAX_TITLE_LENGTH + MAX_DESCRIPTION_LENGTH + 8 + 8 + 8 + 8 + 8 + (1 + 32) + 1 + ContestRules::SIZE + 1 + 32 + 4 + 4 + 8 + 1 + 8 + (4 + 32 * MAX_JUDGES) + 4 + 4 + 1;
+
+    // 手数料をプラットフォーム手数料と賞金プール分に分割する
+    pub fn split_fee(&self, amount: u64) -> (u64, u64) {
+        // 大きな金額でも桁あふれしないよう u128 で計算する
+        let platform_fee = (amount as u128)
+            .checked_mul(self.platform_fee_percentage as u128)
+            .unwrap()
+            .checked_div(100)
+            .unwrap();
+        let platform_fee = u64::try_from(platform_fee).unwrap();
+        let prize_amount = amount.checked_sub(platform_fee).unwrap();
+
+        (platform_fee, prize_amount)
+    }
+
+    // ステータスを変更し、確定・中止時には受取期限を設定する
+    pub fn set_status(&mut self, new_status: ContestStatus, current_time: i64) {
+        if matches!(new_status, ContestStatus::Finalized | ContestStatus::Cancelled) {
+            self.claim_deadline = current_time.checked_add(CLAIM_PERIOD).unwrap();
+        }
+        self.status = new_status;
+    }
+
+    // 順位付けの対象になるエントリー数（失格になったエントリーを除く）
+    pub fn eligible_entry_count(&self) -> u32 {
+        self.entry_count.checked_sub(self.disqualified_entry_count).unwrap()
+    }
+
+    // 投票の受付が完全に終わる時刻（秘密投票では公開期間の終了時刻）
+    pub fn reveal_end_time(&self) -> i64 {
+        self.voting_end_time.checked_add(self.rules.reveal_period).unwrap()
+    }
+
+    // 作成者は常に、それ以外はタイムアウト経過後に集計を実行できる
+    pub fn can_finalize(&self, caller: &Pubkey, current_time: i64) -> bool {
+        *caller == self.authority
+            || current_time >= self.reveal_end_time().checked_add(FINALIZATION_TIMEOUT).unwrap()
+    }
+}
+
+// コンテスト作成時に指定するルール
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ContestRules {
+    pub max_votes_per_voter: u8,        // 1人の投票者がこのコンテストで投票できる回数
+    pub prize_schedule: Vec<u16>,       // 順位ごとの賞金分配率（basis points、合計10,000）
+    pub voting_mode: VotingMode,        // 投票の重み付け方法
+    pub reveal_period: i64,             // 秘密投票の公開期間（秒、0は公開投票）
+    pub judge_weight_bps: u16,          // 順位決定における審査員スコアの比重（basis points、残りは一般投票）
+    pub tie_break: TieBreakPolicy,      // 同点のエントリーの順位の決め方
+    pub max_entries_per_participant: u8, // 1人の参加者がこのコンテストに提出できるエントリー数
+    pub withdrawal_refund_bps: u16,     // エントリー取り下げ時に返金する参加費の割合（basis points）
+    pub disqualification_policy: DisqualificationPolicy, // 失格になったエントリーの参加費の扱い
+}
+
+impl ContestRules {
+    pub const SIZE: usize = 1 + (4 + 2 * MAX_PRIZE_PLACES) + VotingMode::SIZE + 8 + 2 + 1 + 1 + 2 + 1;
+
+    // 投票をコミットして後から公開する秘密投票かどうか
+    pub fn is_secret_ballot(&self) -> bool {
+        self.reveal_period > 0
+    }
+
+    // エントリー取り下げ時に返金する額
+    pub fn withdrawal_refund(&self, entry_fee: u64) -> u64 {
+        let refund = (entry_fee as u128)
+            .checked_mul(self.withdrawal_refund_bps as u128)
+            .unwrap()
+            .checked_div(BASIS_POINTS as u128)
+            .unwrap();
+
+        u64::try_from(refund).unwrap()
+    }
+
+    // 賞金を受け取る順位の数
+    pub fn prize_places(&self) -> usize {
+        self.prize_schedule.len()
+    }
+
+    // 指定した順位（1始まり）の賞金額
+    // 同順位のエントリーが複数ある場合は、占める順位の分配率の合計を等分する
+    // 投票開始後の失格で埋まらなかった順位の分配率はトレジャリーに残り、
+    // 受取期限後に sweep_treasury でプラットフォームに送られる
+    pub fn prize_amount(&self, prize_pool: u64, rank: u8, tie_count: u32) -> u64 {
+        let first_place = rank as usize - 1;
+        let last_place = (first_place + tie_count as usize).min(self.prize_places());
+        let share: u64 = self.prize_schedule[first_place..last_place]
+            .iter()
+            .map(|share| *share as u64)
+            .sum();
+
+        // 賞金プールと分配率の積は u64 を超えうるため u128 で計算する
+        let amount = (prize_pool as u128)
+            .checked_mul(share as u128)
+            .unwrap()
+            .checked_div(BASIS_POINTS as u128)
+            .unwrap()
+            .checked_div(tie_count as u128)
+            .unwrap();
+
+        u64::try_from(amount).unwrap()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum TieBreakPolicy {
+    EarliestEntry,   // 先に提出されたエントリーを上位にする
+    MostVoters,      // 投票数（vote_count）が多いエントリーを上位にし、それでも同じなら先に提出された方
+    SplitPrize,      // 同順位とし、占める順位の賞金を等分する
+}
+
+impl TieBreakPolicy {
+    // 同点のエントリーの並び順（Equalは同順位）
+    pub fn compare(&self, a: &EntryAccount, b: &EntryAccount) -> Ordering {
+        match self {
+            TieBreakPolicy::EarliestEntry => a.timestamp.cmp(&b.timestamp),
+            TieBreakPolicy::MostVoters => b
+                .vote_count
+                .cmp(&a.vote_count)
+                .then(a.timestamp.cmp(&b.timestamp)),
+            TieBreakPolicy::SplitPrize => Ordering::Equal,
+        }
+    }
+
+    // 同点のエントリーを同順位にするかどうか
+    pub fn splits_prize(&self) -> bool {
+        *self == TieBreakPolicy::SplitPrize
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum DisqualificationPolicy {
+    Refund,      // 参加費を参加者に返金する
+    Forfeit,     // 参加費の全額を賞金プールに没収する
+    Slash,       // 参加費の全額をプラットフォーム手数料として没収する
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum VotingMode {
+    Linear,                                     // 投票料金×票数を支払い、票数がそのまま重みになる
+    Quadratic,                                  // 投票料金×票数²を支払い、票数が重みになる（重みは支払額の平方根に比例）
+    TokenLock { governance_mint: Pubkey },      // ガバナンストークンをエスクローにロックし、ロック量が重みになる
+}
+
+impl VotingMode {
+    pub const SIZE: usize = 1 + 32;
+
+    // 票数に対して支払う投票料金（オーバーフロー時はNone）
+    pub fn vote_cost(&self, vote_fee: u64, votes: u64) -> Option<u64> {
+        match self {
+            VotingMode::Linear => vote_fee.checked_mul(votes),
+            VotingMode::Quadratic => vote_fee.checked_mul(votes.checked_mul(votes)?),
+            VotingMode::TokenLock { .. } => Some(vote_fee),
+        }
+    }
+
+    // トークンロック投票で使用するガバナンストークンのMint
+    pub fn governance_mint(&self) -> Option<Pubkey> {
+        match self {
+            VotingMode::TokenLock { governance_mint } => Some(*governance_mint),
+            _ => None,
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -59,6 +244,8 @@ pub enum ContestStatus {
     Voting,      // エントリー終了、投票中
     Closed,      // 投票終了、集計中
     Finalized,   // 結果確定、賞金分配済み
+    Cancelled,   // 中止、参加費・投票料金は返金可能
+    Revealing,   // 秘密投票の公開期間（投票終了後、集計前）
 }
 
 #[account]
@@ -70,11 +257,83 @@ pub struct EntryAccount {
     pub vote_count: u32,                // このエントリーへの投票数
     pub weighted_votes: u64,            // トークン加重された投票値
     pub rank: Option<u8>,               // 最終ランキング（Noneは未決定）
+    pub entry_fee: u64,                 // 支払ったエントリー料金（lamports）
+    pub prize_claimed: bool,            // 賞金を受け取り済みかどうか
+    pub judge_score: u64,               // 審査員スコアの合計
+    pub tie_count: u32,                 // 同順位のエントリー数（自身を含む、順位未決定の場合は0）
+    pub index: u32,                     // コンテスト内のエントリー番号（PDAのシード）
+    pub content_hash: [u8; 32],         // エントリーコンテンツのハッシュ（投票開始後は変更不可）
+    pub vote_fees: u64,                 // このエントリーへの有効な投票の投票料金の合計
+    pub vote_platform_fees: u64,        // vote_feesのうちプラットフォーム手数料分
+    pub disqualification_reason: Option<u8>, // 失格理由コード（Noneは失格でない）
     pub bump: u8,                       // PDA bump seed
 }
 
 impl EntryAccount {
-    pub const SIZE: usize = 32 + 32 + MAX_URI_LENGTH + 8 + 4 + 8 + (1 + 1) + 1;
+    pub const SIZE: usize = 32 + 32 + MAX_URI_LENGTH + 8 + 4 + 8 + (1 + 1) + 8 + 1 + 8 + 4 + 4 + 32 + 8 + 8 + (1 + 1) + 1;
+
+    // 失格になりランキングの対象外かどうか
+    pub fn is_disqualified(&self) -> bool {
+        self.disqualification_reason.is_some()
+    }
+
+    // 有効な投票の投票料金を記録する（失格時に投票者へ返金するため）
+    pub fn record_vote_fee(&mut self, vote_fee: u64, platform_fee: u64) {
+        self.vote_fees = self.vote_fees.checked_add(vote_fee).unwrap();
+        self.vote_platform_fees = self.vote_platform_fees.checked_add(platform_fee).unwrap();
+    }
+}
+
+// エントリーの変更履歴（直近MAX_ENTRY_REVISIONS件の以前の内容）
+#[account]
+pub struct EntryRevisions {
+    pub entry: Pubkey,                  // 対象のエントリー
+    pub revision_count: u32,            // これまでの変更回数（現在の内容のリビジョン番号）
+    pub revisions: Vec<EntryRevision>,  // 以前の内容（古い順、上限を超えると最も古いものから削除）
+    pub bump: u8,                       // PDA bump seed
+}
+
+impl EntryRevisions {
+    pub const SIZE: usize = 32 + 4 + (4 + EntryRevision::SIZE * MAX_ENTRY_REVISIONS) + 1;
+
+    // 置き換えられる内容を現在のリビジョン番号で履歴に追加する
+    pub fn record(&mut self, content_uri: String, content_hash: [u8; 32], replaced_at: i64) {
+        if self.revisions.len() == MAX_ENTRY_REVISIONS {
+            self.revisions.remove(0);
+        }
+        self.revisions.push(EntryRevision {
+            revision: self.revision_count,
+            content_uri,
+            content_hash,
+            replaced_at,
+        });
+        self.revision_count = self.revision_count.checked_add(1).unwrap();
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct EntryRevision {
+    pub revision: u32,                  // この内容のリビジョン番号（0は提出時の内容）
+    pub content_uri: String,            // 以前のコンテンツURI
+    pub content_hash: [u8; 32],         // 以前のコンテンツハッシュ
+    pub replaced_at: i64,               // この内容が置き換えられたタイムスタンプ
+}
+
+impl EntryRevision {
+    pub const SIZE: usize = 4 + (4 + MAX_URI_LENGTH) + 32 + 8;
+}
+
+#[account]
+pub struct JudgeScore {
+    pub contest: Pubkey,                // 対象のコンテスト
+    pub entry: Pubkey,                  // 採点されたエントリー
+    pub judge: Pubkey,                  // 採点した審査員
+    pub score: u8,                      // スコア（0-MAX_JUDGE_SCORE）
+    pub bump: u8,                       // PDA bump seed
+}
+
+impl JudgeScore {
+    pub const SIZE: usize = 32 + 32 + 32 + 1 + 1;
 }
 
 #[account]
@@ -84,11 +343,68 @@ pub struct VoteAccount {
     pub entry: Pubkey,                  // 投票対象のエントリー
     pub timestamp: i64,                 // 投票時のタイムスタンプ
     pub token_amount: u64,              // 投票に使用したトークン量
+    pub vote_fee: u64,                  // 支払った投票料金（lamports）
+    pub locked_amount: u64,             // エスクローにロック中のガバナンストークン量
+    pub commitment: Option<[u8; 32]>,   // 秘密投票のコミットメント（エントリー・票数・ソルトのハッシュ）
+    pub revealed: bool,                 // 秘密投票が公開済みかどうか
     pub bump: u8,                       // PDA bump seed
 }
 
 impl VoteAccount {
-    pub const SIZE: usize = 32 + 32 + 32 + 8 + 8 + 1;
+    pub const SIZE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + (1 + 32) + 1 + 1;
+
+    // PDAのシード（秘密投票ではコミットメント、公開投票ではエントリー）
+    pub fn seed(&self) -> [u8; 32] {
+        self.commitment.unwrap_or_else(|| self.entry.to_bytes())
+    }
+
+    // 公開されなかった秘密投票かどうか（料金は全額賞金プールに入る）
+    pub fn is_unrevealed(&self) -> bool {
+        self.commitment.is_some() && !self.revealed
+    }
+}
+
+// 秘密投票のコミットメントを計算する
+// 投票者とコンテストを含めることで、他人のコミットメントを複製して公開時に再利用できないようにする
+pub fn vote_commitment(
+    voter: &Pubkey,
+    contest: &Pubkey,
+    entry: &Pubkey,
+    token_amount: u64,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[
+        voter.as_ref(),
+        contest.as_ref(),
+        entry.as_ref(),
+        &token_amount.to_le_bytes(),
+        salt,
+    ])
+    .to_bytes()
+}
+
+#[account]
+pub struct VoterRecord {
+    pub contest: Pubkey,                // 対象のコンテスト
+    pub voter: Pubkey,                  // 投票者のアドレス
+    pub votes_cast: u8,                 // このコンテストでの投票回数
+    pub bump: u8,                       // PDA bump seed
+}
+
+impl VoterRecord {
+    pub const SIZE: usize = 32 + 32 + 1 + 1;
+}
+
+#[account]
+pub struct ParticipantRecord {
+    pub contest: Pubkey,                // 対象のコンテスト
+    pub participant: Pubkey,            // 参加者のアドレス
+    pub entry_indices: Vec<u32>,        // 提出したエントリーの番号（エントリーPDAの列挙用）
+    pub bump: u8,                       // PDA bump seed
+}
+
+impl ParticipantRecord {
+    pub const SIZE: usize = 32 + 32 + (4 + 4 * MAX_ENTRIES_PER_PARTICIPANT) + 1;
 }
 
 #[account]
@@ -98,10 +414,11 @@ pub struct TreasuryAccount {
     pub total_funds: u64,               // 保管されている総資金（lamports）
     pub platform_fee: u64,              // プラットフォーム手数料分（lamports）
     pub prize_pool: u64,                // 賞金プール分（lamports）
-    pub is_distributed: bool,           // 賞金が分配済みかどうか
+    pub prizes_claimed: u64,            // 受け取り済みの賞金の合計（lamports）
+    pub platform_fee_withdrawn: bool,   // プラットフォーム手数料が引き出し済みかどうか
     pub bump: u8,                       // PDA bump seed
 }
 
 impl TreasuryAccount {
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 1 + 1;
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1;
 } 
\ No newline at end of file
@@ -0,0 +1,234 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { BankrunProvider, ContestBank, startContestBank } from "./utils/bankrun";
+import {
+  TestContest,
+  entryFee,
+  initializePlatform,
+  noTokenAccounts,
+  setPlatformPause,
+} from "./utils/contest";
+
+describe("Cancellation Tests", () => {
+  let bank: ContestBank;
+  let provider: BankrunProvider;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participant1 = anchor.web3.Keypair.generate();
+  const participant2 = anchor.web3.Keypair.generate();
+  const participant3 = anchor.web3.Keypair.generate();
+  const voter = anchor.web3.Keypair.generate();
+  const stranger = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for transaction fees, entry fees and vote fees on a bank
+    // whose clock we control
+    bank = await startContestBank([
+      payer,
+      participant1,
+      participant2,
+      participant3,
+      voter,
+      stranger,
+    ]);
+    ({ provider, program } = bank);
+  });
+
+  let platformConfigPDA: PublicKey;
+  let contest: TestContest;
+  let contestPDA: PublicKey;
+  let treasuryPDA: PublicKey;
+  let entryPDA1: PublicKey;
+
+  const cancelContest = (caller: anchor.web3.Keypair, target: PublicKey) =>
+    program.methods
+      .cancelContest()
+      .accountsPartial({
+        caller: caller.publicKey,
+        contest: target,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([caller])
+      .rpc();
+
+  it("Sets up a live contest with entries", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Cancellation Test Contest",
+      description: "Testing contest cancellation and refunds",
+    });
+    contestPDA = contest.contest;
+    treasuryPDA = contest.treasury;
+
+    await contest.open();
+    entryPDA1 = await contest.submitEntry(participant1, "https://example.com/entry1");
+    await contest.submitEntry(participant2, "https://example.com/entry2");
+
+    const entry = await program.account.entryAccount.fetch(entryPDA1);
+    expect(entry.entryFee.toString()).to.equal(entryFee.toString());
+  });
+
+  it("Rejects cancellation by other wallets before the grace period", async () => {
+    try {
+      await cancelContest(stranger, contestPDA);
+      expect.fail("cancel_contest should require the creator before the grace period");
+    } catch (error) {
+      expect(error.toString()).to.contain("CancellationNotAllowed");
+    }
+  });
+
+  it("Rejects refunds before the contest is cancelled", async () => {
+    try {
+      await program.methods
+        .claimEntryRefund()
+        .accountsPartial({
+          ...noTokenAccounts,
+          participant: participant1.publicKey,
+          contest: contestPDA,
+          entry: entryPDA1,
+          entryRevisions: null,
+          treasury: treasuryPDA,
+        })
+        .signers([participant1])
+        .rpc();
+      expect.fail("claim_entry_refund should require a cancelled contest");
+    } catch (error) {
+      expect(error.toString()).to.contain("ContestNotCancelled");
+    }
+  });
+
+  it("Rejects cancellation while contest management is paused", async () => {
+    // Pause only contest management (bit 0)
+    await setPlatformPause(program, payer, false, 1);
+
+    try {
+      await cancelContest(payer, contestPDA);
+      expect.fail("cancel_contest should be rejected while contest management is paused");
+    } catch (error) {
+      expect(error.toString()).to.contain("ProgramPaused");
+    }
+
+    await setPlatformPause(program, payer, false, 0);
+  });
+
+  it("Cancels the contest as its creator", async () => {
+    await cancelContest(payer, contestPDA);
+
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.status).to.deep.equal({ cancelled: {} });
+  });
+
+  it("Refunds the exact entry fee and closes the entry", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    const initialTreasuryBalance = await provider.connection.getBalance(treasuryPDA);
+
+    await program.methods
+      .claimEntryRefund()
+      .accountsPartial({
+        ...noTokenAccounts,
+        participant: participant1.publicKey,
+        contest: contestPDA,
+        entry: entryPDA1,
+        entryRevisions: null,
+        treasury: treasuryPDA,
+      })
+      .signers([participant1])
+      .rpc();
+
+    // The entry account is closed
+    const closedEntry = await provider.connection.getAccountInfo(entryPDA1);
+    expect(closedEntry).to.equal(null);
+
+    // The treasury paid out exactly the entry fee and its bookkeeping is reversed
+    const finalTreasuryBalance = await provider.connection.getBalance(treasuryPDA);
+    expect(initialTreasuryBalance - finalTreasuryBalance).to.equal(entryFee.toNumber());
+
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(
+      initialTreasury.totalFunds.sub(entryFee).toString()
+    );
+    expect(treasury.totalFunds.toString()).to.equal(
+      treasury.platformFee.add(treasury.prizePool).toString()
+    );
+  });
+
+  it("Rejects a second refund for the same entry", async () => {
+    try {
+      await program.methods
+        .claimEntryRefund()
+        .accountsPartial({
+          ...noTokenAccounts,
+          participant: participant1.publicKey,
+          contest: contestPDA,
+          entry: entryPDA1,
+          entryRevisions: null,
+          treasury: treasuryPDA,
+        })
+        .signers([participant1])
+        .rpc();
+      expect.fail("claim_entry_refund should fail for a closed entry");
+    } catch (error) {
+      expect(error.toString()).to.not.contain("should fail");
+    }
+  });
+
+  it("Lets anyone cancel a contest stuck past its grace period", async () => {
+    // Two entries are too few to open voting, so the contest stays live
+    const stuckContest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Stuck Contest",
+      description: "Never reaches voting",
+    });
+    await stuckContest.open();
+    await stuckContest.submitEntry(participant1);
+    await stuckContest.submitEntry(participant2);
+
+    // The grace period runs for a week after voting ends
+    const gracePeriod = 60 * 60 * 24 * 7;
+    await bank.warpTo(stuckContest.votingEndTime + gracePeriod);
+
+    try {
+      await cancelContest(stranger, stuckContest.contest);
+      expect.fail("cancel_contest should wait for the whole grace period");
+    } catch (error) {
+      expect(error.toString()).to.contain("CancellationNotAllowed");
+    }
+
+    await bank.warpTo(stuckContest.votingEndTime + gracePeriod + 1);
+    await cancelContest(stranger, stuckContest.contest);
+
+    const contestAccount = await stuckContest.fetch();
+    expect(contestAccount.status).to.deep.equal({ cancelled: {} });
+  });
+
+  it("Rejects cancelling a contest whose winners are decided", async () => {
+    const countedContest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Counted Contest",
+      description: "Closed with its winners decided",
+    });
+    await countedContest.open();
+    for (const participant of [participant1, participant2, participant3]) {
+      await countedContest.submitEntry(participant);
+    }
+    await countedContest.startVoting();
+    await countedContest.castVote(voter, countedContest.entries[0]);
+    await countedContest.close();
+    await countedContest.countVotes();
+
+    // Left unfinalized past the grace period, its prizes are still owed
+    await bank.warpTo(countedContest.votingEndTime + 60 * 60 * 24 * 7 + 1);
+
+    try {
+      await cancelContest(stranger, countedContest.contest);
+      expect.fail("cancel_contest should not cancel a contest with decided winners");
+    } catch (error) {
+      expect(error.toString()).to.contain("CancellationNotAllowed");
+    }
+
+    const contestAccount = await countedContest.fetch();
+    expect(contestAccount.status).to.deep.equal({ closed: {} });
+  });
+});
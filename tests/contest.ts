@@ -1,16 +1,16 @@
-import * as anchor from "@project-serum/anchor";
-import { Program } from "@project-serum/anchor";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
 import { ContestProgram } from "../target/types/contest_program";
-import { MeameraceContract } from "../target/types/memerace_contract";
 import { expect } from "chai";
+import { contestRules, noTokenAccounts } from "./utils/contest";
 
 describe("Contest Management Tests", () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
-  const program = anchor.workspace.MeameraceContract as Program<MeameraceContract>;
+  const program = anchor.workspace.ContestProgram as Program<ContestProgram>;
   const payer = anchor.web3.Keypair.generate();
 
   before(async () => {
@@ -39,8 +39,8 @@ describe("Contest Management Tests", () => {
     const minVoteFee = new anchor.BN(10000000); // 0.01 SOL
 
     await program.methods
-      .initializePlatform(platformFeePercentage, minEntryFee, minVoteFee)
-      .accounts({
+      .initializePlatform(platformFeePercentage, minEntryFee, minVoteFee, payer.publicKey)
+      .accountsPartial({
         authority: payer.publicKey,
         platformConfig: platformConfigPDA,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -64,6 +64,118 @@ describe("Contest Management Tests", () => {
       minVoteFee.toString()
     );
     expect(platformConfig.bump).to.equal(configBump);
+    expect(platformConfig.feeRecipient.toString()).to.equal(
+      payer.publicKey.toString()
+    );
+  });
+
+  it("Updates the platform configuration", async () => {
+    const newMinEntryFee = new anchor.BN(150000000); // 0.15 SOL
+
+    await program.methods
+      .updatePlatformConfig(null, newMinEntryFee, null, null)
+      .accountsPartial({
+        authority: payer.publicKey,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+
+    const platformConfig = await program.account.platformConfig.fetch(
+      platformConfigPDA
+    );
+    expect(platformConfig.minEntryFee.toString()).to.equal(
+      newMinEntryFee.toString()
+    );
+    expect(platformConfig.platformFeePercentage).to.equal(10);
+
+    // Restore the original minimum entry fee for the remaining tests
+    await program.methods
+      .updatePlatformConfig(null, new anchor.BN(100000000), null, null)
+      .accountsPartial({
+        authority: payer.publicKey,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+  });
+
+  it("Rejects platform configuration updates from other wallets", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .updatePlatformConfig(50, null, null, null)
+        .accountsPartial({
+          authority: stranger.publicKey,
+          platformConfig: platformConfigPDA,
+        })
+        .signers([stranger])
+        .rpc();
+      expect.fail("update_platform_config should require the platform authority");
+    } catch (error) {
+      expect(error.toString()).to.contain("Unauthorized");
+    }
+  });
+
+  it("Transfers the platform authority in two steps", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .proposePlatformAuthority(newAuthority.publicKey)
+      .accountsPartial({
+        authority: payer.publicKey,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+
+    // The current authority stays in control until the proposal is accepted
+    let platformConfig = await program.account.platformConfig.fetch(
+      platformConfigPDA
+    );
+    expect(platformConfig.authority.toString()).to.equal(
+      payer.publicKey.toString()
+    );
+    expect(platformConfig.pendingAuthority.toString()).to.equal(
+      newAuthority.publicKey.toString()
+    );
+
+    await program.methods
+      .acceptPlatformAuthority()
+      .accountsPartial({
+        newAuthority: newAuthority.publicKey,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    platformConfig = await program.account.platformConfig.fetch(
+      platformConfigPDA
+    );
+    expect(platformConfig.authority.toString()).to.equal(
+      newAuthority.publicKey.toString()
+    );
+    expect(platformConfig.pendingAuthority).to.equal(null);
+
+    // Hand the authority back to the payer for the remaining tests
+    await program.methods
+      .proposePlatformAuthority(payer.publicKey)
+      .accountsPartial({
+        authority: newAuthority.publicKey,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    await program.methods
+      .acceptPlatformAuthority()
+      .accountsPartial({
+        newAuthority: payer.publicKey,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
   });
 
   let contestId: Uint8Array;
@@ -94,7 +206,8 @@ describe("Contest Management Tests", () => {
     // Get current timestamp
     const now = Math.floor(Date.now() / 1000);
     const startTime = new anchor.BN(now + 100); // start in 100 seconds
-    const endTime = new anchor.BN(now + 86400); // end in 1 day
+    const entryEndTime = new anchor.BN(now + 43200); // entries close in 12 hours
+    const votingEndTime = new anchor.BN(now + 86400); // voting ends in 1 day
     const entryFee = new anchor.BN(200000000); // 0.2 SOL
     const voteFee = new anchor.BN(50000000); // 0.05 SOL
 
@@ -107,11 +220,15 @@ describe("Contest Management Tests", () => {
         title,
         description,
         startTime,
-        endTime,
+        entryEndTime,
+        votingEndTime,
         entryFee,
-        voteFee
+        voteFee,
+        contestRules(),
+        []
       )
-      .accounts({
+      .accountsPartial({
+        ...noTokenAccounts,
         authority: payer.publicKey,
         contest: contestPDA,
         treasury: treasuryPDA,
@@ -128,9 +245,11 @@ describe("Contest Management Tests", () => {
     expect(contest.title).to.equal(title);
     expect(contest.description).to.equal(description);
     expect(contest.startTime.toString()).to.equal(startTime.toString());
-    expect(contest.endTime.toString()).to.equal(endTime.toString());
+    expect(contest.entryEndTime.toString()).to.equal(entryEndTime.toString());
+    expect(contest.votingEndTime.toString()).to.equal(votingEndTime.toString());
     expect(contest.entryFee.toString()).to.equal(entryFee.toString());
     expect(contest.voteFee.toString()).to.equal(voteFee.toString());
+    expect(contest.platformFeePercentage).to.equal(10);
     expect(contest.status).to.deep.equal({ upcoming: {} });
     expect(contest.treasury.toString()).to.equal(treasuryPDA.toString());
     expect(contest.entryCount).to.equal(0);
@@ -139,24 +258,191 @@ describe("Contest Management Tests", () => {
     expect(contest.winnersDecided).to.equal(false);
   });
 
+  it("Rejects a contest whose voting window ends before entries close", async () => {
+    const badContestId = anchor.web3.Keypair.generate().publicKey.toBuffer();
+    const [badContestPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("contest"), badContestId],
+      program.programId
+    );
+    const [badTreasuryPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("treasury"), badContestPDA.toBuffer()],
+      program.programId
+    );
+
+    const now = Math.floor(Date.now() / 1000);
+
+    try {
+      await program.methods
+        .createContest(
+          Array.from(badContestId),
+          "Invalid Schedule",
+          "Voting ends before entries close",
+          new anchor.BN(now + 100),
+          new anchor.BN(now + 86400),
+          new anchor.BN(now + 43200),
+          new anchor.BN(200000000),
+          new anchor.BN(50000000),
+          contestRules(),
+          []
+        )
+        .accountsPartial({
+          ...noTokenAccounts,
+          authority: payer.publicKey,
+          contest: badContestPDA,
+          treasury: badTreasuryPDA,
+          platformConfig: platformConfigPDA,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([payer])
+        .rpc();
+      expect.fail("create_contest should reject an invalid schedule");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidTimestamp");
+    }
+  });
+
+  it("Rejects a prize schedule that does not add up to the whole pool", async () => {
+    const badContestId = anchor.web3.Keypair.generate().publicKey.toBuffer();
+    const [badContestPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("contest"), badContestId],
+      program.programId
+    );
+    const [badTreasuryPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("treasury"), badContestPDA.toBuffer()],
+      program.programId
+    );
+
+    const now = Math.floor(Date.now() / 1000);
+
+    try {
+      await program.methods
+        .createContest(
+          Array.from(badContestId),
+          "Invalid Prize Schedule",
+          "Prize shares only add up to 90%",
+          new anchor.BN(now + 100),
+          new anchor.BN(now + 43200),
+          new anchor.BN(now + 86400),
+          new anchor.BN(200000000),
+          new anchor.BN(50000000),
+          contestRules({ prizeSchedule: [6000, 3000] }),
+          []
+        )
+        .accountsPartial({
+          ...noTokenAccounts,
+          authority: payer.publicKey,
+          contest: badContestPDA,
+          treasury: badTreasuryPDA,
+          platformConfig: platformConfigPDA,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([payer])
+        .rpc();
+      expect.fail("create_contest should reject an incomplete prize schedule");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidContestRules");
+    }
+  });
+
   it("Updates a contest", async () => {
     const newTitle = "Updated Test Contest";
     const newDescription = "This is an updated test contest description";
 
-    await program.methods
-      .updateContest(newTitle, newDescription, null, null)
-      .accounts({
+    const signature = await program.methods
+      .updateContest(newTitle, newDescription, null, null, null)
+      .accountsPartial({
         authority: payer.publicKey,
         contest: contestPDA,
+        platformConfig: platformConfigPDA,
       })
       .signers([payer])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     // Fetch the contest account
     const contest = await program.account.contestAccount.fetch(contestPDA);
 
     expect(contest.title).to.equal(newTitle);
     expect(contest.description).to.equal(newDescription);
+
+    // Events are emitted through a self-CPI, so they are read from the inner instructions
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+    });
+    const eventIx = tx.meta.innerInstructions[0].instructions[0];
+    const eventData = anchor.utils.bytes.bs58.decode(eventIx.data).subarray(8);
+    const event = program.coder.events.decode(
+      anchor.utils.bytes.base64.encode(eventData)
+    );
+
+    expect(event.name).to.equal("ContestUpdated");
+    expect(event.data.contest.toString()).to.equal(contestPDA.toString());
+    expect(event.data.title).to.equal(newTitle);
+  });
+
+  it("Rejects pausing the platform from other wallets", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .setPlatformPause(true, 0)
+        .accountsPartial({
+          authority: stranger.publicKey,
+          platformConfig: platformConfigPDA,
+        })
+        .signers([stranger])
+        .rpc();
+      expect.fail("set_platform_pause should require the platform authority");
+    } catch (error) {
+      expect(error.toString()).to.contain("Unauthorized");
+    }
+  });
+
+  it("Pauses instructions until the platform authority unpauses them", async () => {
+    const updateContest = () =>
+      program.methods
+        .updateContest("Paused Contest", null, null, null, null)
+        .accountsPartial({
+          authority: payer.publicKey,
+          contest: contestPDA,
+          platformConfig: platformConfigPDA,
+        })
+        .signers([payer])
+        .rpc();
+    const setPlatformPause = (paused: boolean, pausedInstructions: number) =>
+      program.methods
+        .setPlatformPause(paused, pausedInstructions)
+        .accountsPartial({
+          authority: payer.publicKey,
+          platformConfig: platformConfigPDA,
+        })
+        .signers([payer])
+        .rpc();
+
+    // Pause only contest management (bit 0)
+    await setPlatformPause(false, 1);
+
+    try {
+      await updateContest();
+      expect.fail("update_contest should be rejected while contest management is paused");
+    } catch (error) {
+      expect(error.toString()).to.contain("ProgramPaused");
+    }
+
+    // The global flag pauses everything regardless of the mask
+    await setPlatformPause(true, 0);
+
+    try {
+      await updateContest();
+      expect.fail("update_contest should be rejected while the platform is paused");
+    } catch (error) {
+      expect(error.toString()).to.contain("ProgramPaused");
+    }
+
+    await setPlatformPause(false, 0);
+    await updateContest();
+
+    const contest = await program.account.contestAccount.fetch(contestPDA);
+    expect(contest.title).to.equal("Paused Contest");
   });
 
   it("Updates contest status to Live", async () => {
@@ -166,9 +452,10 @@ describe("Contest Management Tests", () => {
     
     await program.methods
       .updateContestStatus({ live: {} })
-      .accounts({
+      .accountsPartial({
         authority: payer.publicKey,
         contest: contestPDA,
+        platformConfig: platformConfigPDA,
       })
       .signers([payer])
       .rpc();
@@ -177,4 +464,26 @@ describe("Contest Management Tests", () => {
     const contest = await program.account.contestAccount.fetch(contestPDA);
     expect(contest.status).to.deep.equal({ live: {} });
   });
+
+  it("Rejects advancing the contest before the entry window closes", async () => {
+    const cranker = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .advanceContest()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          contest: contestPDA,
+          platformConfig: platformConfigPDA,
+        })
+        .signers([cranker])
+        .rpc();
+      expect.fail("advance_contest should wait for the entry window to close");
+    } catch (error) {
+      expect(error.toString()).to.contain("EntryPeriodNotEnded");
+    }
+
+    const contest = await program.account.contestAccount.fetch(contestPDA);
+    expect(contest.status).to.deep.equal({ live: {} });
+  });
 });
@@ -1,33 +1,37 @@
-import * as anchor from "@project-serum/anchor";
-import { Program } from "@project-serum/anchor";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
 import { ContestProgram } from "../target/types/contest_program";
 import { expect } from "chai";
+import { BankrunProvider, ContestBank, startContestBank } from "./utils/bankrun";
+import {
+  TestContest,
+  contentHash,
+  entryFee,
+  initializePlatform,
+  noTokenAccounts,
+  setPlatformPause,
+} from "./utils/contest";
 
 describe("Entry Management Tests", () => {
-  // Configure the client to use the local cluster
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
-
-  const program = anchor.workspace.ContestProgram as Program<ContestProgram>;
+  let bank: ContestBank;
+  let provider: BankrunProvider;
+  let program: Program<ContestProgram>;
   const payer = anchor.web3.Keypair.generate();
   const participant1 = anchor.web3.Keypair.generate();
   const participant2 = anchor.web3.Keypair.generate();
   const participant3 = anchor.web3.Keypair.generate();
 
   before(async () => {
-    // Airdrop SOL to participants for transaction fees and entry fees
-    for (const user of [payer, participant1, participant2, participant3]) {
-      const signature = await provider.connection.requestAirdrop(
-        user.publicKey,
-        10 * anchor.web3.LAMPORTS_PER_SOL
-      );
-      await provider.connection.confirmTransaction(signature);
-    }
+    // Fund participants for transaction fees and entry fees on a bank whose
+    // clock we control
+    bank = await startContestBank([payer, participant1, participant2, participant3]);
+    ({ provider, program } = bank);
   });
 
   let platformConfigPDA: PublicKey;
-  let contestId: Uint8Array;
+  let contest: TestContest;
   let contestPDA: PublicKey;
   let treasuryPDA: PublicKey;
   let entryPDA1: PublicKey;
@@ -35,107 +39,29 @@ describe("Entry Management Tests", () => {
   let entryPDA3: PublicKey;
 
   it("Sets up the test environment", async () => {
-    // Initialize platform config
-    const [configPDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("platform-config")],
-      program.programId
-    );
-    platformConfigPDA = configPDA;
-
-    await program.methods
-      .initializePlatform(10, new anchor.BN(100000000), new anchor.BN(10000000))
-      .accounts({
-        authority: payer.publicKey,
-        platformConfig: platformConfigPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([payer])
-      .rpc();
+    platformConfigPDA = await initializePlatform(program, payer);
 
-    // Create a contest
-    contestId = anchor.web3.Keypair.generate().publicKey.toBuffer();
-    const [cPDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("contest"), contestId],
-      program.programId
-    );
-    contestPDA = cPDA;
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Entry Test Contest",
+      description: "Testing entry submissions",
+      rules: { maxEntriesPerParticipant: 2, withdrawalRefundBps: 5000 },
+    });
+    contestPDA = contest.contest;
+    treasuryPDA = contest.treasury;
 
-    const [tPDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("treasury"), contestPDA.toBuffer()],
-      program.programId
-    );
-    treasuryPDA = tPDA;
-
-    // Get current timestamp
-    const now = Math.floor(Date.now() / 1000);
-    const startTime = new anchor.BN(now - 100); // started 100 seconds ago
-    const endTime = new anchor.BN(now + 86400); // ends in 1 day
-    const entryFee = new anchor.BN(200000000); // 0.2 SOL
-    const voteFee = new anchor.BN(50000000); // 0.05 SOL
-
-    await program.methods
-      .createContest(
-        Array.from(contestId),
-        "Entry Test Contest",
-        "Testing entry submissions",
-        startTime,
-        endTime,
-        entryFee,
-        voteFee
-      )
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-        treasury: treasuryPDA,
-        platformConfig: platformConfigPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([payer])
-      .rpc();
-
-    // Update contest status to Live
-    await program.methods
-      .updateContestStatus({ live: {} })
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-      })
-      .signers([payer])
+    // Open the contest for entries
+    await contest.open();
 
     // Find PDAs for entries
-    const [e1PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("entry"), contestPDA.toBuffer(), participant1.publicKey.toBuffer()],
-      program.programId
-    );
-    entryPDA1 = e1PDA;
-
-    const [e2PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("entry"), contestPDA.toBuffer(), participant2.publicKey.toBuffer()],
-      program.programId
-    );
-    entryPDA2 = e2PDA;
-
-    const [e3PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("entry"), contestPDA.toBuffer(), participant3.publicKey.toBuffer()],
-      program.programId
-    );
-    entryPDA3 = e3PDA;
+    entryPDA1 = contest.entryAddress(0);
+    entryPDA2 = contest.entryAddress(1);
+    entryPDA3 = contest.entryAddress(2);
   });
 
   it("Submits an entry to the contest", async () => {
     const contentUri = "https://example.com/entry1";
 
-    await program.methods
-      .submitEntry(contentUri)
-      .accounts({
-        participant: participant1.publicKey,
-        contest: contestPDA,
-        entry: entryPDA1,
-        treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([participant1])
-      .rpc();
+    await contest.submitEntry(participant1, contentUri);
 
     // Fetch the entry account
     const entry = await program.account.entryAccount.fetch(entryPDA1);
@@ -143,24 +69,33 @@ describe("Entry Management Tests", () => {
     expect(entry.contest.toString()).to.equal(contestPDA.toString());
     expect(entry.participant.toString()).to.equal(participant1.publicKey.toString());
     expect(entry.contentUri).to.equal(contentUri);
+    expect(entry.contentHash).to.deep.equal(contentHash);
     expect(entry.voteCount).to.equal(0);
     expect(entry.weightedVotes.toNumber()).to.equal(0);
     expect(entry.rank).to.equal(null);
 
     // Fetch the contest account to check entry count
-    const contest = await program.account.contestAccount.fetch(contestPDA);
-    expect(contest.entryCount).to.equal(1);
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.entryCount).to.equal(1);
   });
 
   it("Updates an entry", async () => {
     const newContentUri = "https://example.com/entry1-updated";
+    const newContentHash = Array.from(createHash("sha256").update("updated entry content").digest());
+    const [entryRevisionsPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("entry-revisions"), entryPDA1.toBuffer()],
+      program.programId
+    );
 
     await program.methods
-      .updateEntry(newContentUri)
-      .accounts({
+      .updateEntry(newContentUri, newContentHash)
+      .accountsPartial({
         participant: participant1.publicKey,
         contest: contestPDA,
+        platformConfig: platformConfigPDA,
         entry: entryPDA1,
+        entryRevisions: entryRevisionsPDA,
+        systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([participant1])
       .rpc();
@@ -168,44 +103,188 @@ describe("Entry Management Tests", () => {
     // Fetch the updated entry account
     const entry = await program.account.entryAccount.fetch(entryPDA1);
     expect(entry.contentUri).to.equal(newContentUri);
+    expect(entry.contentHash).to.deep.equal(newContentHash);
+
+    // The submitted content is kept as revision 0
+    const entryRevisions = await program.account.entryRevisions.fetch(entryRevisionsPDA);
+    expect(entryRevisions.entry.toString()).to.equal(entryPDA1.toString());
+    expect(entryRevisions.revisionCount).to.equal(1);
+    expect(entryRevisions.revisions.length).to.equal(1);
+    expect(entryRevisions.revisions[0].revision).to.equal(0);
+    expect(entryRevisions.revisions[0].contentUri).to.equal("https://example.com/entry1");
+    expect(entryRevisions.revisions[0].contentHash).to.deep.equal(contentHash);
   });
 
   it("Submits multiple entries from different participants", async () => {
-    // Participant 2 submits an entry
+    await contest.submitEntry(participant2, "https://example.com/entry2");
+    await contest.submitEntry(participant3, "https://example.com/entry3");
+
+    // Fetch the contest account to check entry count
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.entryCount).to.equal(3);
+
+    // Fetch the treasury account to check funds
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    const expectedTotalFunds = entryFee.mul(new anchor.BN(3));
+    
+    expect(treasury.totalFunds.toString()).to.equal(expectedTotalFunds.toString());
+
+    // The platform fee follows the percentage snapshotted into the contest
+    const contestFeePercentage = new anchor.BN(contestAccount.platformFeePercentage);
+    const expectedPlatformFee = entryFee.mul(contestFeePercentage).div(new anchor.BN(100)).mul(new anchor.BN(3));
+    expect(treasury.platformFee.toString()).to.equal(expectedPlatformFee.toString());
+    expect(treasury.prizePool.toString()).to.equal(
+      expectedTotalFunds.sub(expectedPlatformFee).toString()
+    );
+  });
+
+  it("Lets a participant submit several entries up to the contest limit", async () => {
+    const participantRecordPDA1 = contest.participantRecordAddress(participant1.publicKey);
+
+    // The fourth entry of the contest is participant 1's second
+    const entryPDA4 = await contest.submitEntry(participant1, "https://example.com/entry4");
+
+    const entry = await program.account.entryAccount.fetch(entryPDA4);
+    expect(entry.index).to.equal(3);
+    expect(entry.participant.toString()).to.equal(participant1.publicKey.toString());
+
+    // The participant record lists the numbers of all of the participant's entries
+    const participantRecord = await program.account.participantRecord.fetch(participantRecordPDA1);
+    expect(participantRecord.entryIndices).to.deep.equal([0, 3]);
+
+    // A third entry exceeds the limit of two
+    try {
+      await contest.submitEntry(participant1, "https://example.com/entry5");
+      expect.fail("submit_entry should reject entries past the participant limit");
+    } catch (error) {
+      expect(error.toString()).to.contain("EntryLimitReached");
+    }
+  });
+
+  it("Rejects withdrawals while entries are paused", async () => {
+    // Pause only entries (bit 1)
+    await setPlatformPause(program, payer, false, 1 << 1);
+
+    try {
+      await program.methods
+        .withdrawEntry()
+        .accountsPartial({
+          ...noTokenAccounts,
+          participant: participant2.publicKey,
+          contest: contestPDA,
+          platformConfig: platformConfigPDA,
+          entry: entryPDA2,
+          entryRevisions: null,
+          participantRecord: contest.participantRecordAddress(participant2.publicKey),
+          treasury: treasuryPDA,
+        })
+        .signers([participant2])
+        .rpc();
+      expect.fail("withdraw_entry should be rejected while entries are paused");
+    } catch (error) {
+      expect(error.toString()).to.contain("ProgramPaused");
+    }
+
+    await setPlatformPause(program, payer, false, 0);
+  });
+
+  it("Withdraws an entry and refunds half of its fee", async () => {
+    const participantRecordPDA2 = contest.participantRecordAddress(participant2.publicKey);
+
+    const initialContest = await contest.fetch();
+    const initialTreasuryBalance = await provider.connection.getBalance(treasuryPDA);
+
     await program.methods
-      .submitEntry("https://example.com/entry2")
-      .accounts({
+      .withdrawEntry()
+      .accountsPartial({
+        ...noTokenAccounts,
         participant: participant2.publicKey,
         contest: contestPDA,
+        platformConfig: platformConfigPDA,
         entry: entryPDA2,
+        entryRevisions: null,
+        participantRecord: participantRecordPDA2,
         treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([participant2])
       .rpc();
 
-    // Participant 3 submits an entry
+    // The entry account is closed and no longer counted
+    const closedEntry = await provider.connection.getAccountInfo(entryPDA2);
+    expect(closedEntry).to.equal(null);
+
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.entryCount).to.equal(initialContest.entryCount - 1);
+
+    // Half of the 0.2 SOL entry fee is refunded
+    const finalTreasuryBalance = await provider.connection.getBalance(treasuryPDA);
+    expect(initialTreasuryBalance - finalTreasuryBalance).to.equal(entryFee.divn(2).toNumber());
+
+    // The kept half is still split between the platform and the prize pool
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(
+      treasury.platformFee.add(treasury.prizePool).toString()
+    );
+    expect(contestAccount.totalPrizePool.toString()).to.equal(treasury.prizePool.toString());
+
+    const participantRecord = await program.account.participantRecord.fetch(participantRecordPDA2);
+    expect(participantRecord.entryIndices).to.deep.equal([]);
+  });
+
+  it("Rejects withdrawing another participant's entry", async () => {
+    const participantRecordPDA3 = contest.participantRecordAddress(participant3.publicKey);
+
+    try {
+      await program.methods
+        .withdrawEntry()
+        .accountsPartial({
+          ...noTokenAccounts,
+          participant: participant1.publicKey,
+          contest: contestPDA,
+          platformConfig: platformConfigPDA,
+          entry: entryPDA3,
+          entryRevisions: null,
+          participantRecord: participantRecordPDA3,
+          treasury: treasuryPDA,
+        })
+        .signers([participant1])
+        .rpc();
+      expect.fail("withdraw_entry should only accept the entry's participant");
+    } catch (error) {
+      expect(error.toString()).to.not.contain("should only accept");
+    }
+  });
+
+  it("Closes the revision log of a withdrawn entry", async () => {
+    const [entryRevisionsPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("entry-revisions"), entryPDA1.toBuffer()],
+      program.programId
+    );
+    const revisionsRent = await provider.connection.getBalance(entryRevisionsPDA);
+    const initialParticipantBalance = await provider.connection.getBalance(participant1.publicKey);
+
     await program.methods
-      .submitEntry("https://example.com/entry3")
-      .accounts({
-        participant: participant3.publicKey,
+      .withdrawEntry()
+      .accountsPartial({
+        ...noTokenAccounts,
+        participant: participant1.publicKey,
         contest: contestPDA,
-        entry: entryPDA3,
+        platformConfig: platformConfigPDA,
+        entry: entryPDA1,
+        entryRevisions: entryRevisionsPDA,
+        participantRecord: contest.participantRecordAddress(participant1.publicKey),
         treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .signers([participant3])
+      .signers([participant1])
       .rpc();
 
-    // Fetch the contest account to check entry count
-    const contest = await program.account.contestAccount.fetch(contestPDA);
-    expect(contest.entryCount).to.equal(3);
+    // Both the entry and its revision log are closed to the participant
+    expect(await provider.connection.getAccountInfo(entryPDA1)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(entryRevisionsPDA)).to.equal(null);
 
-    // Fetch the treasury account to check funds
-    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
-    const entryFee = new anchor.BN(200000000); // 0.2 SOL
-    const expectedTotalFunds = entryFee.mul(new anchor.BN(3));
-    
-    expect(treasury.totalFunds.toString()).to.equal(expectedTotalFunds.toString());
+    const finalParticipantBalance = await provider.connection.getBalance(participant1.publicKey);
+    expect(finalParticipantBalance - initialParticipantBalance).to.be.greaterThan(
+      revisionsRent + entryFee.divn(2).toNumber()
+    );
   });
 });
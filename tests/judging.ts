@@ -0,0 +1,153 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { ContestBank, startContestBank } from "./utils/bankrun";
+import { TestContest, initializePlatform } from "./utils/contest";
+
+describe("Judging Tests", () => {
+  let bank: ContestBank;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participants = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const voters = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const judge1 = anchor.web3.Keypair.generate();
+  const judge2 = anchor.web3.Keypair.generate();
+  const stranger = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for entry fees and vote fees on a bank whose clock we control
+    bank = await startContestBank([payer, ...participants, ...voters, judge1, judge2, stranger]);
+    ({ program } = bank);
+  });
+
+  let platformConfigPDA: PublicKey;
+  let contest: TestContest;
+  let entryPDAs: PublicKey[];
+
+  const submitJudgeScore = async (judge: anchor.web3.Keypair, entryPDA: PublicKey, score: number) => {
+    const [judgeScorePDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("judge-score"), entryPDA.toBuffer(), judge.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .submitJudgeScore(score)
+      .accountsPartial({
+        judge: judge.publicKey,
+        contest: contest.contest,
+        platformConfig: platformConfigPDA,
+        entry: entryPDA,
+        judgeScore: judgeScorePDA,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([judge])
+      .rpc();
+
+    return judgeScorePDA;
+  };
+
+  it("Rejects judge weight without a judge panel", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+
+    try {
+      await TestContest.create(bank, payer, platformConfigPDA, {
+        title: "Invalid Judging Contest",
+        description: "Judges carry weight but there are none",
+        rules: { judgeWeightBps: 6000 },
+      });
+      expect.fail("create_contest should reject judge weight without judges");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidContestRules");
+    }
+  });
+
+  it("Sets up a contest judged 60% by the panel and 40% by public votes", async () => {
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Judged Contest",
+      description: "Ranked by a judge panel and public votes",
+      rules: { judgeWeightBps: 6000 },
+      judges: [judge1.publicKey, judge2.publicKey],
+    });
+
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.judges.map((judge) => judge.toString())).to.deep.equal([
+      judge1.publicKey.toString(),
+      judge2.publicKey.toString(),
+    ]);
+
+    await contest.open();
+    for (const participant of participants) {
+      await contest.submitEntry(participant);
+    }
+    entryPDAs = contest.entries;
+    await contest.startVoting();
+
+    // The public favours entry 1 (2 votes) over entry 3 (1 vote)
+    await contest.castVote(voters[0], entryPDAs[0]);
+    await contest.castVote(voters[1], entryPDAs[0]);
+    await contest.castVote(voters[2], entryPDAs[2]);
+  });
+
+  it("Rejects scores from wallets outside the panel", async () => {
+    try {
+      await submitJudgeScore(stranger, entryPDAs[0], 100);
+      expect.fail("submit_judge_score should require a judge");
+    } catch (error) {
+      expect(error.toString()).to.contain("NotAJudge");
+    }
+  });
+
+  it("Rejects scores above the maximum", async () => {
+    try {
+      await submitJudgeScore(judge1, entryPDAs[0], 101);
+      expect.fail("submit_judge_score should reject scores above 100");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidJudgeScore");
+    }
+  });
+
+  it("Records judge scores and replaces a judge's earlier score", async () => {
+    await submitJudgeScore(judge1, entryPDAs[1], 50);
+    const judgeScorePDA = await submitJudgeScore(judge1, entryPDAs[1], 90);
+    await submitJudgeScore(judge2, entryPDAs[1], 80);
+    await submitJudgeScore(judge1, entryPDAs[0], 10);
+    await submitJudgeScore(judge2, entryPDAs[2], 20);
+
+    const judgeScore = await program.account.judgeScore.fetch(judgeScorePDA);
+    expect(judgeScore.score).to.equal(90);
+    expect(judgeScore.judge.toString()).to.equal(judge1.publicKey.toString());
+
+    // Only the latest score of each judge counts
+    const entry = await program.account.entryAccount.fetch(entryPDAs[1]);
+    expect(entry.judgeScore.toString()).to.equal("170");
+  });
+
+  it("Ranks entries by the blend of judge scores and public votes", async () => {
+    await contest.close();
+    await contest.countVotes();
+
+    // Entry 2: 0.6 * 170/200 + 0.4 * 0/3 = 0.51
+    // Entry 1: 0.6 * 10/200  + 0.4 * 2/3 = 0.297
+    // Entry 3: 0.6 * 20/200  + 0.4 * 1/3 = 0.193
+    expect(await contest.ranks()).to.deep.equal([2, 1, 3]);
+  });
+
+  it("Rejects scores once the ranking is decided", async () => {
+    try {
+      await submitJudgeScore(judge2, entryPDAs[0], 100);
+      expect.fail("submit_judge_score should reject scores after counting");
+    } catch (error) {
+      expect(error.toString()).to.contain("JudgingClosed");
+    }
+  });
+});
@@ -0,0 +1,190 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { BankrunProvider, ContestBank, startContestBank } from "./utils/bankrun";
+import {
+  TestContest,
+  entryFee,
+  initializePlatform,
+  noTokenAccounts,
+  voteFee,
+} from "./utils/contest";
+
+describe("Moderation Tests", () => {
+  let bank: ContestBank;
+  let provider: BankrunProvider;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participants = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const voter1 = anchor.web3.Keypair.generate();
+  const voter2 = anchor.web3.Keypair.generate();
+  const stranger = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for entry fees and vote fees on a bank whose clock we control
+    bank = await startContestBank([payer, ...participants, voter1, voter2, stranger]);
+    ({ provider, program } = bank);
+  });
+
+  const plagiarismReason = 2;
+
+  let platformConfigPDA: PublicKey;
+  let contest: TestContest;
+  let treasuryPDA: PublicKey;
+  let entryPDAs: PublicKey[];
+  let votePDA1: PublicKey;
+
+  const disqualifyEntry = async (
+    authority: anchor.web3.Keypair,
+    index: number,
+    target: TestContest = contest
+  ) => {
+    await program.methods
+      .disqualifyEntry(plagiarismReason)
+      .accountsPartial({
+        ...noTokenAccounts,
+        authority: authority.publicKey,
+        platformConfig: platformConfigPDA,
+        contest: target.contest,
+        entry: target.entries[index],
+        participant: participants[index].publicKey,
+        treasury: target.treasury,
+      })
+      .signers([authority])
+      .rpc();
+  };
+
+  it("Sets up a contest that slashes disqualified entries", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Moderated Contest",
+      description: "Plagiarized entries are disqualified",
+      rules: { disqualificationPolicy: { slash: {} } },
+    });
+    treasuryPDA = contest.treasury;
+
+    await contest.open();
+    for (const participant of participants) {
+      await contest.submitEntry(participant);
+    }
+    entryPDAs = contest.entries;
+    await contest.startVoting();
+
+    votePDA1 = await contest.castVote(voter1, entryPDAs[0]);
+  });
+
+  it("Rejects disqualification by anyone but the contest or platform authority", async () => {
+    try {
+      await disqualifyEntry(stranger, 0);
+      expect.fail("disqualify_entry should require an authority");
+    } catch (error) {
+      expect(error.toString()).to.contain("Unauthorized");
+    }
+  });
+
+  it("Disqualifies an entry and slashes its fee to the platform", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+
+    await disqualifyEntry(payer, 0);
+
+    const entry = await program.account.entryAccount.fetch(entryPDAs[0]);
+    expect(entry.disqualificationReason).to.equal(plagiarismReason);
+
+    // The entry fee's prize share goes to the platform, and the vote fee is
+    // taken out of both shares until the voter reclaims it
+    const entryPrizeShare = entryFee.sub(entryFee.muln(10).divn(100));
+    const votePlatformFee = voteFee.muln(10).divn(100);
+    const votePrizeShare = voteFee.sub(votePlatformFee);
+
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(initialTreasury.totalFunds.toString());
+    expect(treasury.platformFee.toString()).to.equal(
+      initialTreasury.platformFee.add(entryPrizeShare).sub(votePlatformFee).toString()
+    );
+    expect(treasury.prizePool.toString()).to.equal(
+      initialTreasury.prizePool.sub(entryPrizeShare).sub(votePrizeShare).toString()
+    );
+  });
+
+  it("Rejects votes for a disqualified entry", async () => {
+    try {
+      await contest.castVote(voter2, entryPDAs[0]);
+      expect.fail("cast_vote should reject a disqualified entry");
+    } catch (error) {
+      expect(error.toString()).to.contain("EntryDisqualified");
+    }
+  });
+
+  it("Refunds votes cast for a disqualified entry", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    const initialVoterBalance = await provider.connection.getBalance(voter1.publicKey);
+
+    await program.methods
+      .claimVoteRefund()
+      .accountsPartial({
+        ...noTokenAccounts,
+        voter: voter1.publicKey,
+        contest: contest.contest,
+        vote: votePDA1,
+        entry: entryPDAs[0],
+        treasury: treasuryPDA,
+      })
+      .signers([voter1])
+      .rpc();
+
+    // The vote account is closed and the voter gets the fee back
+    const closedVote = await provider.connection.getAccountInfo(votePDA1);
+    expect(closedVote).to.equal(null);
+
+    const finalVoterBalance = await provider.connection.getBalance(voter1.publicKey);
+    expect(finalVoterBalance).to.be.greaterThan(initialVoterBalance + voteFee.toNumber() - 10000);
+
+    // Only the total funds change, the split was reversed on disqualification
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(
+      initialTreasury.totalFunds.sub(voteFee).toString()
+    );
+    expect(treasury.platformFee.toString()).to.equal(initialTreasury.platformFee.toString());
+    expect(treasury.prizePool.toString()).to.equal(initialTreasury.prizePool.toString());
+  });
+
+  it("Leaves disqualified entries out of the ranking", async () => {
+    await contest.castVote(voter2, entryPDAs[2]);
+
+    await contest.close();
+    await contest.countVotes();
+
+    expect(await contest.ranks()).to.deep.equal([null, 2, 1]);
+  });
+
+  it("Leaves disqualified entries out of the minimum to start voting", async () => {
+    const understaffedContest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Understaffed Contest",
+      description: "One of three entries is disqualified",
+    });
+    await understaffedContest.open();
+    for (const participant of participants) {
+      await understaffedContest.submitEntry(participant);
+    }
+    await disqualifyEntry(payer, 0, understaffedContest);
+
+    // Two eligible entries are too few to fill the three prize places
+    try {
+      await understaffedContest.startVoting();
+      expect.fail("update_contest_status should not count disqualified entries");
+    } catch (error) {
+      expect(error.toString()).to.contain("MinimumEntriesNotMet");
+    }
+
+    const contestAccount = await understaffedContest.fetch();
+    expect(contestAccount.entryCount).to.equal(3);
+    expect(contestAccount.disqualifiedEntryCount).to.equal(1);
+  });
+});
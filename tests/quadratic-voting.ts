@@ -0,0 +1,98 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { ContestBank, startContestBank } from "./utils/bankrun";
+import { TestContest, initializePlatform } from "./utils/contest";
+
+describe("Quadratic Voting Tests", () => {
+  let bank: ContestBank;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participants = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const voter1 = anchor.web3.Keypair.generate();
+  const voter2 = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for entry fees and vote fees on a bank whose clock we control
+    bank = await startContestBank([payer, ...participants, voter1, voter2]);
+    ({ program } = bank);
+  });
+
+  const voteFee = new anchor.BN(10000000); // 0.01 SOL for the first vote
+
+  let contest: TestContest;
+  let treasuryPDA: PublicKey;
+  let entryPDAs: PublicKey[];
+
+  it("Sets up a quadratic voting contest", async () => {
+    const platformConfigPDA = await initializePlatform(program, payer);
+
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Quadratic Contest",
+      description: "Votes cost the square of the votes bought",
+      voteFee,
+      rules: { maxVotesPerVoter: 2, votingMode: { quadratic: {} } },
+    });
+    treasuryPDA = contest.treasury;
+
+    await contest.open();
+    for (const participant of participants) {
+      await contest.submitEntry(participant);
+    }
+    entryPDAs = contest.entries;
+    await contest.startVoting();
+  });
+
+  it("Charges the square of the votes bought and weights by the votes", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+
+    // 3 votes cost 9 times the vote fee, 1 vote costs the vote fee
+    const votePDA1 = await contest.castVote(voter1, entryPDAs[0], new anchor.BN(3));
+    const votePDA2 = await contest.castVote(voter2, entryPDAs[1], new anchor.BN(1));
+
+    const vote1 = await program.account.voteAccount.fetch(votePDA1);
+    expect(vote1.voteFee.toString()).to.equal(voteFee.muln(9).toString());
+
+    const vote2 = await program.account.voteAccount.fetch(votePDA2);
+    expect(vote2.voteFee.toString()).to.equal(voteFee.toString());
+
+    // Paying 9 times more only buys 3 times the weight
+    const entry1 = await program.account.entryAccount.fetch(entryPDAs[0]);
+    expect(entry1.weightedVotes.toString()).to.equal("3");
+
+    const entry2 = await program.account.entryAccount.fetch(entryPDAs[1]);
+    expect(entry2.weightedVotes.toString()).to.equal("1");
+
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(
+      initialTreasury.totalFunds.add(voteFee.muln(10)).toString()
+    );
+  });
+
+  it("Prices votes per voter per entry", async () => {
+    // Voter 1 buys 2 votes for a different entry, priced from zero again
+    const votePDA = await contest.castVote(voter1, entryPDAs[2], new anchor.BN(2));
+
+    const vote = await program.account.voteAccount.fetch(votePDA);
+    expect(vote.voteFee.toString()).to.equal(voteFee.muln(4).toString());
+
+    const entry = await program.account.entryAccount.fetch(entryPDAs[2]);
+    expect(entry.weightedVotes.toString()).to.equal("2");
+  });
+
+  it("Rejects vote amounts whose cost overflows", async () => {
+    try {
+      // (2^32)^2 votes overflow u64 before the vote fee is applied
+      await contest.castVote(voter2, entryPDAs[2], new anchor.BN(2).pow(new anchor.BN(32)));
+      expect.fail("cast_vote should reject a cost that does not fit in u64");
+    } catch (error) {
+      expect(error.toString()).to.contain("VoteAmountTooLarge");
+    }
+  });
+});
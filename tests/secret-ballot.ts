@@ -0,0 +1,273 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { createHash, randomBytes } from "crypto";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { ContestBank, startContestBank } from "./utils/bankrun";
+import {
+  TestContest,
+  initializePlatform,
+  noTokenAccounts,
+  noVoteEscrow,
+  voteFee,
+} from "./utils/contest";
+
+describe("Secret Ballot Tests", () => {
+  let bank: ContestBank;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participants = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const voter1 = anchor.web3.Keypair.generate();
+  const voter2 = anchor.web3.Keypair.generate();
+  const copycat = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for entry fees and vote fees on a bank whose clock we control
+    bank = await startContestBank([payer, ...participants, voter1, voter2, copycat]);
+    ({ program } = bank);
+  });
+
+  const tokenAmount = new anchor.BN(1);
+  const salt1 = randomBytes(32);
+  const salt2 = randomBytes(32);
+  const revealPeriod = 3600;
+
+  let platformConfigPDA: PublicKey;
+  let contest: TestContest;
+  let contestPDA: PublicKey;
+  let treasuryPDA: PublicKey;
+  let entryPDAs: PublicKey[];
+  let votePDA1: PublicKey;
+  let votePDA2: PublicKey;
+  let copycatVotePDA: PublicKey;
+
+  // Same hash as the program: sha256(voter || contest || entry || amount as u64 LE || salt)
+  const commitmentFor = (
+    voter: anchor.web3.Keypair,
+    entryPDA: PublicKey,
+    amount: anchor.BN,
+    salt: Buffer
+  ) =>
+    createHash("sha256")
+      .update(
+        Buffer.concat([
+          voter.publicKey.toBuffer(),
+          contestPDA.toBuffer(),
+          entryPDA.toBuffer(),
+          amount.toArrayLike(Buffer, "le", 8),
+          salt,
+        ])
+      )
+      .digest();
+
+  const commitVote = async (voter: anchor.web3.Keypair, commitment: Buffer) => {
+    const [votePDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("vote"), voter.publicKey.toBuffer(), commitment],
+      program.programId
+    );
+    const [voterRecordPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("vote"), contestPDA.toBuffer(), voter.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .commitVote(Array.from(commitment), tokenAmount)
+      .accountsPartial({
+        ...noTokenAccounts,
+        ...noVoteEscrow,
+        voter: voter.publicKey,
+        contest: contestPDA,
+        platformConfig: platformConfigPDA,
+        vote: votePDA,
+        voterRecord: voterRecordPDA,
+        treasury: treasuryPDA,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([voter])
+      .rpc();
+
+    return votePDA;
+  };
+
+  const revealVote = async (
+    voter: anchor.web3.Keypair,
+    votePDA: PublicKey,
+    entryPDA: PublicKey,
+    salt: Buffer
+  ) => {
+    await program.methods
+      .revealVote(Array.from(salt))
+      .accountsPartial({
+        voter: voter.publicKey,
+        contest: contestPDA,
+        platformConfig: platformConfigPDA,
+        entry: entryPDA,
+        vote: votePDA,
+        treasury: treasuryPDA,
+      })
+      .signers([voter])
+      .rpc();
+  };
+
+  it("Sets up a secret ballot contest", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+
+    // Create a contest with a one hour reveal phase
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Secret Ballot Contest",
+      description: "Votes stay sealed until the reveal phase",
+      rules: { revealPeriod: new anchor.BN(revealPeriod) },
+    });
+    contestPDA = contest.contest;
+    treasuryPDA = contest.treasury;
+
+    await contest.open();
+    for (const participant of participants) {
+      await contest.submitEntry(participant);
+    }
+    entryPDAs = contest.entries;
+    await contest.startVoting();
+  });
+
+  it("Rejects quadratic voting in a secret ballot contest", async () => {
+    // Sealed votes can't be priced per voter per entry
+    try {
+      await TestContest.create(bank, payer, platformConfigPDA, {
+        title: "Quadratic Secret Ballot",
+        description: "Sealed quadratic votes",
+        rules: { votingMode: { quadratic: {} }, revealPeriod: new anchor.BN(3600) },
+      });
+      expect.fail("create_contest should reject a quadratic secret ballot");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidContestRules");
+    }
+  });
+
+  it("Rejects public votes in a secret ballot contest", async () => {
+    try {
+      await contest.castVote(voter1, entryPDAs[0], tokenAmount);
+      expect.fail("cast_vote should be rejected for a secret ballot");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidVotingMethod");
+    }
+  });
+
+  it("Commits sealed votes without revealing the entry", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+
+    votePDA1 = await commitVote(voter1, commitmentFor(voter1, entryPDAs[0], tokenAmount, salt1));
+    votePDA2 = await commitVote(voter2, commitmentFor(voter2, entryPDAs[1], tokenAmount, salt2));
+
+    const vote = await program.account.voteAccount.fetch(votePDA1);
+    expect(vote.entry.toString()).to.equal(PublicKey.default.toString());
+    expect(vote.revealed).to.equal(false);
+
+    // No entry has received votes yet
+    for (const entryPDA of entryPDAs) {
+      const entry = await program.account.entryAccount.fetch(entryPDA);
+      expect(entry.weightedVotes.toString()).to.equal("0");
+    }
+
+    // The whole fee is held in the prize pool until the vote is revealed
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.prizePool.toString()).to.equal(
+      initialTreasury.prizePool.add(voteFee.muln(2)).toString()
+    );
+    expect(treasury.platformFee.toString()).to.equal(initialTreasury.platformFee.toString());
+  });
+
+  it("Lets a copycat commit the same bytes as another voter", async () => {
+    // The commitment is public once committed, so anyone can copy it
+    const vote1 = await program.account.voteAccount.fetch(votePDA1);
+    copycatVotePDA = await commitVote(copycat, Buffer.from(vote1.commitment));
+  });
+
+  it("Requires the reveal phase before closing", async () => {
+    await bank.warpTo(contest.votingEndTime);
+
+    try {
+      await contest.updateStatus({ closed: {} });
+      expect.fail("a secret ballot should not skip the reveal phase");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidStatusTransition");
+    }
+
+    await contest.updateStatus({ revealing: {} });
+
+    expect((await contest.fetch()).status).to.deep.equal({ revealing: {} });
+  });
+
+  it("Rejects a reveal that does not match the commitment", async () => {
+    try {
+      // Voter 1 committed to entry 0, not entry 2
+      await revealVote(voter1, votePDA1, entryPDAs[2], salt1);
+      expect.fail("reveal_vote should reject a different entry");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidCommitment");
+    }
+  });
+
+  it("Counts a revealed vote and moves the platform fee out of the prize pool", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+
+    await revealVote(voter1, votePDA1, entryPDAs[0], salt1);
+
+    const vote = await program.account.voteAccount.fetch(votePDA1);
+    expect(vote.entry.toString()).to.equal(entryPDAs[0].toString());
+    expect(vote.revealed).to.equal(true);
+
+    const entry = await program.account.entryAccount.fetch(entryPDAs[0]);
+    expect(entry.weightedVotes.toString()).to.equal(tokenAmount.toString());
+
+    // 10% platform fee
+    const platformFee = voteFee.muln(10).divn(100);
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.platformFee.toString()).to.equal(
+      initialTreasury.platformFee.add(platformFee).toString()
+    );
+    expect(treasury.prizePool.toString()).to.equal(
+      initialTreasury.prizePool.sub(platformFee).toString()
+    );
+  });
+
+  it("Rejects replaying another voter's revealed salt on a copied commitment", async () => {
+    // The hash covers the voter, so voter 1's salt can't open the copy
+    try {
+      await revealVote(copycat, copycatVotePDA, entryPDAs[0], salt1);
+      expect.fail("reveal_vote should reject a commitment copied from another voter");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidCommitment");
+    }
+  });
+
+  it("Rejects revealing the same vote twice", async () => {
+    try {
+      await revealVote(voter1, votePDA1, entryPDAs[0], salt1);
+      expect.fail("reveal_vote should reject a revealed vote");
+    } catch (error) {
+      expect(error.toString()).to.contain("VoteAlreadyRevealed");
+    }
+  });
+
+  it("Forfeits the fee of an unrevealed vote to the prize pool", async () => {
+    const initialTreasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+
+    await bank.warpTo(contest.votingEndTime + revealPeriod);
+    await contest.updateStatus({ closed: {} });
+
+    // Voter 2 never revealed, so their fee stays in the prize pool uncounted
+    const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
+    expect(treasury.prizePool.toString()).to.equal(initialTreasury.prizePool.toString());
+
+    const vote = await program.account.voteAccount.fetch(votePDA2);
+    expect(vote.revealed).to.equal(false);
+
+    const entry = await program.account.entryAccount.fetch(entryPDAs[1]);
+    expect(entry.weightedVotes.toString()).to.equal("0");
+  });
+});
@@ -0,0 +1,138 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { BankrunProvider, ContestBank, startContestBank } from "./utils/bankrun";
+import { TestContest, initializePlatform, noTokenAccounts } from "./utils/contest";
+
+describe("Tie-Break Tests", () => {
+  let bank: ContestBank;
+  let provider: BankrunProvider;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participants = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const voter1 = anchor.web3.Keypair.generate();
+  const voter2 = anchor.web3.Keypair.generate();
+  const voter3 = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for entry fees and vote fees on a bank whose clock we control
+    bank = await startContestBank([payer, ...participants, voter1, voter2, voter3]);
+    ({ provider, program } = bank);
+  });
+
+  let platformConfigPDA: PublicKey;
+
+  // Runs a contest through its entries, casts the given votes, then counts
+  // and finalizes it
+  const runContest = async (
+    tieBreak: object,
+    votes: (contest: TestContest) => Promise<void>
+  ) => {
+    const contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Tie-Break Contest",
+      description: "Two entries finish level",
+      rules: { tieBreak },
+    });
+    await contest.open();
+
+    // Submit entries a second apart so their timestamps differ
+    for (const participant of participants) {
+      await contest.submitEntry(participant);
+      await bank.warpTo((await bank.now()) + 1);
+    }
+
+    await contest.startVoting();
+    await votes(contest);
+    await contest.close();
+    await contest.countVotes();
+    await contest.finalize();
+
+    return contest;
+  };
+
+  // The first two entries receive one vote each and the third none
+  const runTiedContest = (tieBreak: object) =>
+    runContest(tieBreak, async (contest) => {
+      await contest.castVote(voter1, contest.entries[0]);
+      await contest.castVote(voter2, contest.entries[1]);
+    });
+
+  it("Sets up the platform", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+  });
+
+  it("Ranks the earliest of tied entries first", async () => {
+    const { entries: entryPDAs } = await runTiedContest({ earliestEntry: {} });
+
+    const entries = await Promise.all(
+      entryPDAs.map((entryPDA) => program.account.entryAccount.fetch(entryPDA))
+    );
+    expect(entries.map((entry) => entry.rank)).to.deep.equal([1, 2, 3]);
+    expect(entries.map((entry) => entry.tieCount)).to.deep.equal([1, 1, 1]);
+  });
+
+  it("Ranks the tied entry with the most voters first", async () => {
+    // Both entries end on two weighted votes, the second from two voters
+    const contest = await runContest({ mostVoters: {} }, async (contest) => {
+      await contest.castVote(voter1, contest.entries[0], new anchor.BN(2));
+      await contest.castVote(voter2, contest.entries[1]);
+      await contest.castVote(voter3, contest.entries[1]);
+    });
+
+    const entries = await Promise.all(
+      contest.entries.map((entryPDA) => program.account.entryAccount.fetch(entryPDA))
+    );
+    expect(entries.map((entry) => entry.weightedVotes.toString())).to.deep.equal(["2", "2", "0"]);
+    expect(entries.map((entry) => entry.voteCount)).to.deep.equal([1, 2, 0]);
+
+    // The earlier entry would win under EarliestEntry
+    expect(entries.map((entry) => entry.rank)).to.deep.equal([2, 1, 3]);
+    expect(entries.map((entry) => entry.tieCount)).to.deep.equal([1, 1, 1]);
+  });
+
+  it("Splits the shares of the places tied entries occupy", async () => {
+    const contest = await runTiedContest({ splitPrize: {} });
+    const entryPDAs = contest.entries;
+
+    // Both tied entries share first place, the next entry is third
+    const entries = await Promise.all(
+      entryPDAs.map((entryPDA) => program.account.entryAccount.fetch(entryPDA))
+    );
+    expect(entries.map((entry) => entry.rank)).to.deep.equal([1, 1, 3]);
+    expect(entries.map((entry) => entry.tieCount)).to.deep.equal([2, 2, 1]);
+
+    // Each tied entry receives half of the first and second place shares
+    const prizePool = (await contest.fetchTreasury()).prizePool;
+    const expectedPrizes = [
+      prizePool.muln(5000 + 3000).divn(10000).divn(2),
+      prizePool.muln(5000 + 3000).divn(10000).divn(2),
+      prizePool.muln(2000).divn(10000),
+    ];
+
+    for (let i = 0; i < participants.length; i++) {
+      const initialTreasuryBalance = await provider.connection.getBalance(contest.treasury);
+
+      await program.methods
+        .claimPrize()
+        .accountsPartial({
+          ...noTokenAccounts,
+          participant: participants[i].publicKey,
+          contest: contest.contest,
+          platformConfig: platformConfigPDA,
+          entry: entryPDAs[i],
+          treasury: contest.treasury,
+        })
+        .signers([participants[i]])
+        .rpc();
+
+      const finalTreasuryBalance = await provider.connection.getBalance(contest.treasury);
+      expect(initialTreasuryBalance - finalTreasuryBalance).to.equal(expectedPrizes[i].toNumber());
+    }
+  });
+});
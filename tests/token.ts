@@ -0,0 +1,292 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Transaction } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  ExtensionType,
+  createAssociatedTokenAccountInstruction,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+} from "@solana/spl-token";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { BankrunProvider, ContestBank, startContestBank } from "./utils/bankrun";
+import {
+  TestContest,
+  contentHash,
+  contestRules,
+  initializePlatform,
+  noTokenAccounts,
+} from "./utils/contest";
+
+describe("Token Contest Tests", () => {
+  let bank: ContestBank;
+  let provider: BankrunProvider;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participant1 = anchor.web3.Keypair.generate();
+  const participant2 = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for transaction fees and rent on a bank whose clock we control
+    bank = await startContestBank([payer, participant1, participant2]);
+    ({ provider, program } = bank);
+  });
+
+  const entryFee = new anchor.BN(1_000_000); // 1 token with 6 decimals
+  const voteFee = new anchor.BN(100_000); // 0.1 token
+  const transferFeeBasisPoints = 100; // 1% Token-2022 transfer fee
+
+  let platformConfigPDA: PublicKey;
+
+  // Creates a mint with 6 decimals, optionally with a Token-2022 transfer fee
+  const createMint = async (tokenProgram: PublicKey, transferFee = false) => {
+    const mintKeypair = anchor.web3.Keypair.generate();
+    const mint = mintKeypair.publicKey;
+    const mintLen = getMintLen(transferFee ? [ExtensionType.TransferFeeConfig] : []);
+
+    const transaction = new Transaction().add(
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: payer.publicKey,
+        newAccountPubkey: mint,
+        space: mintLen,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+        programId: tokenProgram,
+      })
+    );
+    if (transferFee) {
+      transaction.add(
+        createInitializeTransferFeeConfigInstruction(
+          mint,
+          payer.publicKey,
+          payer.publicKey,
+          transferFeeBasisPoints,
+          BigInt(1_000_000_000),
+          tokenProgram
+        )
+      );
+    }
+    transaction.add(createInitializeMintInstruction(mint, 6, payer.publicKey, null, tokenProgram));
+
+    await provider.sendAndConfirm(transaction, [payer, mintKeypair]);
+    return mint;
+  };
+
+  // Creates the owner's associated token account and mints 10 tokens to it
+  const fundTokenAccount = async (mint: PublicKey, owner: PublicKey, tokenProgram: PublicKey) => {
+    const tokenAccount = getAssociatedTokenAddressSync(mint, owner, false, tokenProgram);
+
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          payer.publicKey,
+          tokenAccount,
+          owner,
+          mint,
+          tokenProgram
+        ),
+        createMintToInstruction(mint, tokenAccount, payer.publicKey, 10_000_000, [], tokenProgram)
+      ),
+      [payer]
+    );
+    return tokenAccount;
+  };
+
+  // Creates a contest denominated in the given mint and opens it for entries
+  const createTokenContest = async (mint: PublicKey, tokenProgram: PublicKey) => {
+    const { contestId, contest: contestPDA, treasury: treasuryPDA } = TestContest.addresses(program);
+    const treasuryTokenAccount = getAssociatedTokenAddressSync(
+      mint,
+      treasuryPDA,
+      true,
+      tokenProgram
+    );
+
+    const now = await bank.now();
+
+    await program.methods
+      .createContest(
+        Array.from(contestId),
+        "Token Test Contest",
+        "Testing token denominated fees",
+        new anchor.BN(now + 100),
+        new anchor.BN(now + 43200),
+        new anchor.BN(now + 86500),
+        entryFee,
+        voteFee,
+        contestRules(),
+        []
+      )
+      .accountsPartial({
+        ...noTokenAccounts,
+        authority: payer.publicKey,
+        contest: contestPDA,
+        treasury: treasuryPDA,
+        platformConfig: platformConfigPDA,
+        feeMint: mint,
+        treasuryTokenAccount,
+        tokenProgram,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+
+    await bank.warpTo(now + 100);
+
+    await program.methods
+      .updateContestStatus({ live: {} })
+      .accountsPartial({
+        authority: payer.publicKey,
+        contest: contestPDA,
+        platformConfig: platformConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+
+    return { contestPDA, treasuryPDA, treasuryTokenAccount };
+  };
+
+  const submitTokenEntry = async (
+    participant: anchor.web3.Keypair,
+    contest: { contestPDA: PublicKey; treasuryPDA: PublicKey; treasuryTokenAccount: PublicKey },
+    mint: PublicKey,
+    participantTokenAccount: PublicKey,
+    tokenProgram: PublicKey
+  ) => {
+    // Entries are addressed by their number within the contest
+    const { nextEntryIndex } = await program.account.contestAccount.fetch(contest.contestPDA);
+    const [entryPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("entry"),
+        contest.contestPDA.toBuffer(),
+        new anchor.BN(nextEntryIndex).toArrayLike(Buffer, "le", 4),
+      ],
+      program.programId
+    );
+
+    const [participantRecordPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), contest.contestPDA.toBuffer(), participant.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .submitEntry("https://example.com/token-entry", contentHash)
+      .accountsPartial({
+        ...noTokenAccounts,
+        participant: participant.publicKey,
+        contest: contest.contestPDA,
+        platformConfig: platformConfigPDA,
+        entry: entryPDA,
+        participantRecord: participantRecordPDA,
+        treasury: contest.treasuryPDA,
+        feeMint: mint,
+        participantTokenAccount,
+        treasuryTokenAccount: contest.treasuryTokenAccount,
+        tokenProgram,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([participant])
+      .rpc();
+
+    return entryPDA;
+  };
+
+  it("Sets up the platform", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+  });
+
+  it("Collects entry fees in an SPL token", async () => {
+    const mint = await createMint(TOKEN_PROGRAM_ID);
+    const participantTokenAccount = await fundTokenAccount(
+      mint,
+      participant1.publicKey,
+      TOKEN_PROGRAM_ID
+    );
+
+    const contest = await createTokenContest(mint, TOKEN_PROGRAM_ID);
+
+    const contestAccount = await program.account.contestAccount.fetch(contest.contestPDA);
+    expect(contestAccount.feeMint.toString()).to.equal(mint.toString());
+
+    const entryPDA = await submitTokenEntry(
+      participant1,
+      contest,
+      mint,
+      participantTokenAccount,
+      TOKEN_PROGRAM_ID
+    );
+
+    // The full fee arrives in the treasury's token account
+    const treasuryTokenAccount = await getAccount(provider.connection, contest.treasuryTokenAccount);
+    expect(treasuryTokenAccount.amount.toString()).to.equal(entryFee.toString());
+
+    const entry = await program.account.entryAccount.fetch(entryPDA);
+    expect(entry.entryFee.toString()).to.equal(entryFee.toString());
+
+    const treasury = await program.account.treasuryAccount.fetch(contest.treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(entryFee.toString());
+  });
+
+  it("Records the net amount received for Token-2022 transfer fee mints", async () => {
+    // Create a Token-2022 mint with a transfer fee
+    const mint = await createMint(TOKEN_2022_PROGRAM_ID, true);
+    const participantTokenAccount = await fundTokenAccount(
+      mint,
+      participant2.publicKey,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const contest = await createTokenContest(mint, TOKEN_2022_PROGRAM_ID);
+    const entryPDA = await submitTokenEntry(
+      participant2,
+      contest,
+      mint,
+      participantTokenAccount,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    // The transfer fee is withheld, so only the net amount is credited
+    const expectedReceived = entryFee.sub(entryFee.muln(transferFeeBasisPoints).divn(10000));
+
+    const entry = await program.account.entryAccount.fetch(entryPDA);
+    expect(entry.entryFee.toString()).to.equal(expectedReceived.toString());
+
+    const treasury = await program.account.treasuryAccount.fetch(contest.treasuryPDA);
+    expect(treasury.totalFunds.toString()).to.equal(expectedReceived.toString());
+    expect(treasury.totalFunds.toString()).to.equal(
+      treasury.platformFee.add(treasury.prizePool).toString()
+    );
+  });
+
+  it("Rejects token accounts of a different mint", async () => {
+    const mint = await createMint(TOKEN_PROGRAM_ID);
+    const otherMint = await createMint(TOKEN_PROGRAM_ID);
+    const otherTokenAccount = await fundTokenAccount(
+      otherMint,
+      participant1.publicKey,
+      TOKEN_PROGRAM_ID
+    );
+
+    const contest = await createTokenContest(mint, TOKEN_PROGRAM_ID);
+
+    try {
+      await submitTokenEntry(
+        participant1,
+        { ...contest, treasuryTokenAccount: otherTokenAccount },
+        otherMint,
+        otherTokenAccount,
+        TOKEN_PROGRAM_ID
+      );
+      expect.fail("submit_entry should reject a mint other than the contest's fee mint");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidFeeMint");
+    }
+  });
+});
@@ -0,0 +1,114 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Provider } from "@coral-xyz/anchor";
+import { readFileSync } from "fs";
+import {
+  Connection,
+  Keypair,
+  PublicKey,
+  Signer,
+  Transaction,
+  TransactionSignature,
+} from "@solana/web3.js";
+import { BanksClient, Clock, ProgramTestContext, start } from "solana-bankrun";
+import { ContestProgram } from "../../target/types/contest_program";
+
+// Contest schedules span at least a day, so suites that walk a contest through
+// its phases run against an in-process bank whose clock can be moved forward.
+
+// The subset of Connection the tests and the Anchor account client use
+const bankrunConnection = (banksClient: BanksClient) => ({
+  getAccountInfo: async (address: PublicKey) => {
+    const account = await banksClient.getAccount(address);
+    return account && { ...account, data: Buffer.from(account.data) };
+  },
+  getBalance: async (address: PublicKey) => Number(await banksClient.getBalance(address)),
+  getMinimumBalanceForRentExemption: async (dataLength: number) =>
+    Number((await banksClient.getRent()).minimumBalance(BigInt(dataLength))),
+});
+
+// A provider that sends transactions to the bank, paid for by the bank's payer
+export class BankrunProvider implements Provider {
+  readonly connection: Connection;
+  readonly wallet: anchor.Wallet;
+  readonly publicKey: PublicKey;
+
+  constructor(readonly context: ProgramTestContext) {
+    this.connection = bankrunConnection(context.banksClient) as unknown as Connection;
+    this.wallet = new anchor.Wallet(context.payer);
+    this.publicKey = context.payer.publicKey;
+  }
+
+  async sendAndConfirm(tx: Transaction, signers: Signer[] = []): Promise<TransactionSignature> {
+    tx.feePayer = this.context.payer.publicKey;
+    tx.recentBlockhash = (await this.context.banksClient.getLatestBlockhash())[0];
+    tx.sign(this.context.payer, ...signers);
+
+    const { result, meta } = await this.context.banksClient.tryProcessTransaction(tx);
+
+    // Move to a new slot so retrying the same instruction gets a new blockhash
+    this.context.warpToSlot((await this.context.banksClient.getSlot()) + BigInt(1));
+
+    if (result) {
+      // Anchor parses the program logs into an AnchorError
+      const logs = meta?.logMessages ?? [];
+      throw Object.assign(new Error([result, ...logs].join("\n")), { logs });
+    }
+
+    return anchor.utils.bytes.bs58.encode(tx.signature);
+  }
+}
+
+export type ContestBank = {
+  context: ProgramTestContext;
+  provider: BankrunProvider;
+  program: Program<ContestProgram>;
+  // Current unix timestamp of the bank's clock
+  now: () => Promise<number>;
+  // Moves the bank's clock to the given unix timestamp
+  warpTo: (unixTimestamp: number) => Promise<void>;
+};
+
+// Starts a bank with the contest program deployed and each user funded
+export const startContestBank = async (
+  users: Keypair[],
+  lamports = 10 * anchor.web3.LAMPORTS_PER_SOL
+): Promise<ContestBank> => {
+  // Load the program and IDL built by `anchor build`
+  process.env.SBF_OUT_DIR = process.env.SBF_OUT_DIR ?? "target/deploy";
+  const idl: ContestProgram = JSON.parse(
+    readFileSync("target/idl/contest_program.json", "utf8")
+  );
+  const programId = new PublicKey(idl.address);
+
+  const context = await start(
+    [{ name: "contest_program", programId }],
+    users.map((user) => ({
+      address: user.publicKey,
+      info: {
+        lamports,
+        data: Buffer.alloc(0),
+        owner: anchor.web3.SystemProgram.programId,
+        executable: false,
+      },
+    }))
+  );
+  const provider = new BankrunProvider(context);
+  const program = new Program<ContestProgram>(idl, provider);
+
+  const now = async () => Number((await context.banksClient.getClock()).unixTimestamp);
+
+  const warpTo = async (unixTimestamp: number) => {
+    const clock = await context.banksClient.getClock();
+    context.setClock(
+      new Clock(
+        clock.slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        BigInt(unixTimestamp)
+      )
+    );
+  };
+
+  return { context, provider, program, now, warpTo };
+};
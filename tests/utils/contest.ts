@@ -0,0 +1,322 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
+import { ContestProgram } from "../../target/types/contest_program";
+import { ContestBank } from "./bankrun";
+
+// Anchor resolves the accounts a test leaves out, except optional ones, which
+// have to be passed as null. SOL contests use none of the fee token accounts.
+export const noTokenAccounts = {
+  feeMint: null,
+  treasuryTokenAccount: null,
+  tokenProgram: null,
+  associatedTokenProgram: null,
+  participantTokenAccount: null,
+  voterTokenAccount: null,
+  contributorTokenAccount: null,
+  platformWalletTokenAccount: null,
+};
+
+// Votes outside token lock contests lock no governance tokens
+export const noVoteEscrow = {
+  governanceMint: null,
+  voterGovernanceTokenAccount: null,
+  voteEscrow: null,
+  governanceTokenProgram: null,
+};
+
+export const entryFee = new anchor.BN(200000000); // 0.2 SOL
+export const voteFee = new anchor.BN(50000000); // 0.05 SOL
+export const contentHash = Array.from(createHash("sha256").update("entry content").digest());
+
+// Rules of a plain linear voting contest with three prize places, with the
+// given fields replaced
+export const contestRules = (overrides: object = {}) => ({
+  maxVotesPerVoter: 1,
+  prizeSchedule: [5000, 3000, 2000],
+  votingMode: { linear: {} },
+  revealPeriod: new anchor.BN(0),
+  judgeWeightBps: 0,
+  tieBreak: { earliestEntry: {} },
+  maxEntriesPerParticipant: 1,
+  withdrawalRefundBps: 0,
+  disqualificationPolicy: { refund: {} },
+  ...overrides,
+});
+
+export const platformConfigAddress = (program: Program<ContestProgram>) =>
+  PublicKey.findProgramAddressSync([Buffer.from("platform-config")], program.programId)[0];
+
+// Initializes the platform with a 10% fee, paid to the authority unless
+// another fee recipient is given
+export const initializePlatform = async (
+  program: Program<ContestProgram>,
+  authority: Keypair,
+  feeRecipient = authority.publicKey
+): Promise<PublicKey> => {
+  const platformConfigPDA = platformConfigAddress(program);
+
+  await program.methods
+    .initializePlatform(10, new anchor.BN(100000000), new anchor.BN(10000000), feeRecipient)
+    .accountsPartial({
+      authority: authority.publicKey,
+      platformConfig: platformConfigPDA,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+
+  return platformConfigPDA;
+};
+
+export const setPlatformPause = (
+  program: Program<ContestProgram>,
+  authority: Keypair,
+  paused: boolean,
+  pausedInstructions: number
+) =>
+  program.methods
+    .setPlatformPause(paused, pausedInstructions)
+    .accountsPartial({
+      authority: authority.publicKey,
+      platformConfig: platformConfigAddress(program),
+    })
+    .signers([authority])
+    .rpc();
+
+export type ContestStatus = Parameters<Program<ContestProgram>["methods"]["updateContestStatus"]>[0];
+
+export type ContestOptions = {
+  title?: string;
+  description?: string;
+  entryFee?: anchor.BN;
+  voteFee?: anchor.BN;
+  rules?: object;
+  judges?: PublicKey[];
+};
+
+// A SOL contest on a bankrun bank, scheduled from the bank's clock and moved
+// through its phases by warping to each deadline
+export class TestContest {
+  readonly program: Program<ContestProgram>;
+  readonly entries: PublicKey[] = [];
+
+  private constructor(
+    readonly bank: ContestBank,
+    readonly authority: Keypair,
+    readonly platformConfig: PublicKey,
+    readonly contest: PublicKey,
+    readonly treasury: PublicKey,
+    readonly startTime: number,
+    readonly entryEndTime: number,
+    readonly votingEndTime: number
+  ) {
+    this.program = bank.program;
+  }
+
+  // The contest and treasury addresses of a new contest id
+  static addresses(program: Program<ContestProgram>) {
+    const contestId = Keypair.generate().publicKey.toBuffer();
+    const [contest] = PublicKey.findProgramAddressSync(
+      [Buffer.from("contest"), contestId],
+      program.programId
+    );
+    const [treasury] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), contest.toBuffer()],
+      program.programId
+    );
+    return { contestId, contest, treasury };
+  }
+
+  // Creates a contest that starts in 100 seconds, takes entries for half a
+  // day and closes voting a day after creation
+  static async create(
+    bank: ContestBank,
+    authority: Keypair,
+    platformConfig: PublicKey,
+    options: ContestOptions = {}
+  ): Promise<TestContest> {
+    const { program } = bank;
+    const { contestId, contest, treasury } = TestContest.addresses(program);
+
+    const now = await bank.now();
+    const startTime = now + 100;
+    const entryEndTime = now + 43200;
+    const votingEndTime = now + 86500;
+
+    await program.methods
+      .createContest(
+        Array.from(contestId),
+        options.title ?? "Test Contest",
+        options.description ?? "A contest run by the test suite",
+        new anchor.BN(startTime),
+        new anchor.BN(entryEndTime),
+        new anchor.BN(votingEndTime),
+        options.entryFee ?? entryFee,
+        options.voteFee ?? voteFee,
+        contestRules(options.rules),
+        options.judges ?? []
+      )
+      .accountsPartial({
+        ...noTokenAccounts,
+        authority: authority.publicKey,
+        contest,
+        treasury,
+        platformConfig,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    return new TestContest(
+      bank,
+      authority,
+      platformConfig,
+      contest,
+      treasury,
+      startTime,
+      entryEndTime,
+      votingEndTime
+    );
+  }
+
+  entryAddress(index: number) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("entry"),
+        this.contest.toBuffer(),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 4),
+      ],
+      this.program.programId
+    )[0];
+  }
+
+  participantRecordAddress(participant: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), this.contest.toBuffer(), participant.toBuffer()],
+      this.program.programId
+    )[0];
+  }
+
+  voteAddress(voter: PublicKey, entry: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), voter.toBuffer(), entry.toBuffer()],
+      this.program.programId
+    )[0];
+  }
+
+  voterRecordAddress(voter: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), this.contest.toBuffer(), voter.toBuffer()],
+      this.program.programId
+    )[0];
+  }
+
+  async fetch() {
+    return this.program.account.contestAccount.fetch(this.contest);
+  }
+
+  async fetchTreasury() {
+    return this.program.account.treasuryAccount.fetch(this.treasury);
+  }
+
+  async updateStatus(status: ContestStatus, authority = this.authority) {
+    await this.program.methods
+      .updateContestStatus(status)
+      .accountsPartial({
+        authority: authority.publicKey,
+        contest: this.contest,
+        platformConfig: this.platformConfig,
+      })
+      .signers([authority])
+      .rpc();
+  }
+
+  // Warps to the start time and opens the contest for entries
+  async open() {
+    await this.bank.warpTo(this.startTime);
+    await this.updateStatus({ live: {} });
+  }
+
+  async submitEntry(participant: Keypair, uri = "https://example.com/entry"): Promise<PublicKey> {
+    const { nextEntryIndex } = await this.fetch();
+    const entry = this.entryAddress(nextEntryIndex);
+
+    await this.program.methods
+      .submitEntry(uri, contentHash)
+      .accountsPartial({
+        ...noTokenAccounts,
+        participant: participant.publicKey,
+        contest: this.contest,
+        platformConfig: this.platformConfig,
+        entry,
+        participantRecord: this.participantRecordAddress(participant.publicKey),
+        treasury: this.treasury,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([participant])
+      .rpc();
+
+    this.entries.push(entry);
+    return entry;
+  }
+
+  // Warps to the end of the entry period and opens voting
+  async startVoting() {
+    await this.bank.warpTo(this.entryEndTime);
+    await this.updateStatus({ voting: {} });
+  }
+
+  async castVote(voter: Keypair, entry: PublicKey, amount = new anchor.BN(1)): Promise<PublicKey> {
+    const vote = this.voteAddress(voter.publicKey, entry);
+
+    await this.program.methods
+      .castVote(amount)
+      .accountsPartial({
+        ...noTokenAccounts,
+        ...noVoteEscrow,
+        voter: voter.publicKey,
+        contest: this.contest,
+        platformConfig: this.platformConfig,
+        entry,
+        vote,
+        voterRecord: this.voterRecordAddress(voter.publicKey),
+        treasury: this.treasury,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([voter])
+      .rpc();
+
+    return vote;
+  }
+
+  // Warps to the end of voting and closes the contest
+  async close() {
+    await this.bank.warpTo(this.votingEndTime);
+    await this.updateStatus({ closed: {} });
+  }
+
+  async countVotes(entries = this.entries) {
+    await this.program.methods
+      .countVotes()
+      .accountsPartial({
+        authority: this.authority.publicKey,
+        contest: this.contest,
+        platformConfig: this.platformConfig,
+      })
+      .remainingAccounts(entries.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+      .signers([this.authority])
+      .rpc();
+  }
+
+  async finalize() {
+    await this.updateStatus({ finalized: {} });
+  }
+
+  async ranks() {
+    return Promise.all(
+      this.entries.map(async (entry) => (await this.program.account.entryAccount.fetch(entry)).rank)
+    );
+  }
+}
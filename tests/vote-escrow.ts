@@ -0,0 +1,188 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Transaction } from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { ContestProgram } from "../target/types/contest_program";
+import { expect } from "chai";
+import { BankrunProvider, ContestBank, startContestBank } from "./utils/bankrun";
+import { TestContest, initializePlatform, noTokenAccounts, voteFee } from "./utils/contest";
+
+describe("Vote Escrow Tests", () => {
+  let bank: ContestBank;
+  let provider: BankrunProvider;
+  let program: Program<ContestProgram>;
+  const payer = anchor.web3.Keypair.generate();
+  const participants = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
+  const voter = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    // Fund users for entry fees and vote fees on a bank whose clock we control
+    bank = await startContestBank([payer, ...participants, voter]);
+    ({ provider, program } = bank);
+  });
+
+  const lockAmount = new anchor.BN(7_000_000); // 7 governance tokens with 6 decimals
+
+  let platformConfigPDA: PublicKey;
+  let contest: TestContest;
+  let contestPDA: PublicKey;
+  let treasuryPDA: PublicKey;
+  let entryPDAs: PublicKey[];
+  let votePDA: PublicKey;
+  let voterRecordPDA: PublicKey;
+  let voteEscrowPDA: PublicKey;
+  let governanceMint: PublicKey;
+  let voterGovernanceTokenAccount: PublicKey;
+
+  it("Sets up a token lock voting contest", async () => {
+    platformConfigPDA = await initializePlatform(program, payer);
+
+    // Create the governance token and fund the voter
+    const mint = anchor.web3.Keypair.generate();
+    governanceMint = mint.publicKey;
+    voterGovernanceTokenAccount = getAssociatedTokenAddressSync(governanceMint, voter.publicKey);
+
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: payer.publicKey,
+          newAccountPubkey: governanceMint,
+          space: MINT_SIZE,
+          lamports: await getMinimumBalanceForRentExemptMint(provider.connection),
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(governanceMint, 6, payer.publicKey, null),
+        createAssociatedTokenAccountInstruction(
+          payer.publicKey,
+          voterGovernanceTokenAccount,
+          voter.publicKey,
+          governanceMint
+        ),
+        createMintToInstruction(governanceMint, voterGovernanceTokenAccount, payer.publicKey, 10_000_000)
+      ),
+      [payer, mint]
+    );
+
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Token Lock Contest",
+      description: "Votes are weighted by locked governance tokens",
+      rules: { votingMode: { tokenLock: { governanceMint } } },
+    });
+    contestPDA = contest.contest;
+    treasuryPDA = contest.treasury;
+
+    await contest.open();
+    for (const participant of participants) {
+      await contest.submitEntry(participant);
+    }
+    entryPDAs = contest.entries;
+    await contest.startVoting();
+
+    votePDA = contest.voteAddress(voter.publicKey, entryPDAs[0]);
+    voterRecordPDA = contest.voterRecordAddress(voter.publicKey);
+    [voteEscrowPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("vote-escrow"), votePDA.toBuffer()],
+      program.programId
+    );
+  });
+
+  it("Weights a vote by the governance tokens locked in escrow", async () => {
+    await program.methods
+      .castVote(lockAmount)
+      .accountsPartial({
+        ...noTokenAccounts,
+        voter: voter.publicKey,
+        contest: contestPDA,
+        platformConfig: platformConfigPDA,
+        entry: entryPDAs[0],
+        vote: votePDA,
+        voterRecord: voterRecordPDA,
+        treasury: treasuryPDA,
+        governanceMint,
+        voterGovernanceTokenAccount,
+        voteEscrow: voteEscrowPDA,
+        governanceTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([voter])
+      .rpc();
+
+    const escrow = await getAccount(provider.connection, voteEscrowPDA);
+    expect(escrow.amount.toString()).to.equal(lockAmount.toString());
+
+    const vote = await program.account.voteAccount.fetch(votePDA);
+    expect(vote.lockedAmount.toString()).to.equal(lockAmount.toString());
+
+    // The flat vote fee is charged once, the lock amount is the weight
+    expect(vote.voteFee.toString()).to.equal(voteFee.toString());
+
+    const entry = await program.account.entryAccount.fetch(entryPDAs[0]);
+    expect(entry.weightedVotes.toString()).to.equal(lockAmount.toString());
+  });
+
+  it("Keeps the tokens locked until the contest is finalized", async () => {
+    try {
+      await program.methods
+        .unlockVoteTokens()
+        .accountsPartial({
+          voter: voter.publicKey,
+          contest: contestPDA,
+          vote: votePDA,
+          governanceMint,
+          voterGovernanceTokenAccount,
+          voteEscrow: voteEscrowPDA,
+          governanceTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([voter])
+        .rpc();
+      expect.fail("unlock_vote_tokens should wait for the contest to finish");
+    } catch (error) {
+      expect(error.toString()).to.contain("ContestNotEnded");
+    }
+  });
+
+  it("Returns the locked tokens after finalization", async () => {
+    await contest.close();
+    await contest.countVotes();
+    await contest.finalize();
+
+    const initialBalance = (await getAccount(provider.connection, voterGovernanceTokenAccount)).amount;
+
+    await program.methods
+      .unlockVoteTokens()
+      .accountsPartial({
+        voter: voter.publicKey,
+        contest: contestPDA,
+        vote: votePDA,
+        governanceMint,
+        voterGovernanceTokenAccount,
+        voteEscrow: voteEscrowPDA,
+        governanceTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([voter])
+      .rpc();
+
+    const finalBalance = (await getAccount(provider.connection, voterGovernanceTokenAccount)).amount;
+    expect((finalBalance - initialBalance).toString()).to.equal(lockAmount.toString());
+
+    // The escrow is closed and the vote no longer holds a lock
+    const closedEscrow = await provider.connection.getAccountInfo(voteEscrowPDA);
+    expect(closedEscrow).to.equal(null);
+
+    const vote = await program.account.voteAccount.fetch(votePDA);
+    expect(vote.lockedAmount.toString()).to.equal("0");
+  });
+});
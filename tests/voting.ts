@@ -1,204 +1,120 @@
-import * as anchor from "@project-serum/anchor";
-import { Program } from "@project-serum/anchor";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
 import { ContestProgram } from "../target/types/contest_program";
 import { expect } from "chai";
+import { ContestBank, startContestBank } from "./utils/bankrun";
+import { TestContest, contentHash, entryFee, initializePlatform, voteFee } from "./utils/contest";
 
 describe("Voting Tests", () => {
-  // Configure the client to use the local cluster
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
-
-  const program = anchor.workspace.ContestProgram as Program<ContestProgram>;
+  let bank: ContestBank;
+  let program: Program<ContestProgram>;
   const payer = anchor.web3.Keypair.generate();
   const participant1 = anchor.web3.Keypair.generate();
   const participant2 = anchor.web3.Keypair.generate();
+  const participant3 = anchor.web3.Keypair.generate();
   const voter1 = anchor.web3.Keypair.generate();
   const voter2 = anchor.web3.Keypair.generate();
 
   before(async () => {
-    // Airdrop SOL to users for transaction fees, entry fees, and vote fees
-    for (const user of [payer, participant1, participant2, voter1, voter2]) {
-      const signature = await provider.connection.requestAirdrop(
-        user.publicKey,
-        10 * anchor.web3.LAMPORTS_PER_SOL
-      );
-      await provider.connection.confirmTransaction(signature);
-    }
+    // Fund users for transaction fees, entry fees, and vote fees on a bank
+    // whose clock we control
+    bank = await startContestBank([
+      payer,
+      participant1,
+      participant2,
+      participant3,
+      voter1,
+      voter2,
+    ]);
+    ({ program } = bank);
   });
 
   let platformConfigPDA: PublicKey;
-  let contestId: Uint8Array;
+  let contest: TestContest;
   let contestPDA: PublicKey;
   let treasuryPDA: PublicKey;
   let entryPDA1: PublicKey;
   let entryPDA2: PublicKey;
   let votePDA1: PublicKey;
   let votePDA2: PublicKey;
+  let voterRecordPDA1: PublicKey;
 
   it("Sets up the test environment with entries", async () => {
-    // Initialize platform config
-    const [configPDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("platform-config")],
-      program.programId
-    );
-    platformConfigPDA = configPDA;
-
-    await program.methods
-      .initializePlatform(10, new anchor.BN(100000000), new anchor.BN(10000000))
-      .accounts({
-        authority: payer.publicKey,
-        platformConfig: platformConfigPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([payer])
-      .rpc();
-
-    // Create a contest
-    contestId = anchor.web3.Keypair.generate().publicKey.toBuffer();
-    const [cPDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("contest"), contestId],
-      program.programId
-    );
-    contestPDA = cPDA;
-
-    const [tPDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("treasury"), contestPDA.toBuffer()],
-      program.programId
-    );
-    treasuryPDA = tPDA;
-
-    // Get current timestamp
-    const now = Math.floor(Date.now() / 1000);
-    const startTime = new anchor.BN(now - 100); // started 100 seconds ago
-    const endTime = new anchor.BN(now + 86400); // ends in 1 day
-    const entryFee = new anchor.BN(200000000); // 0.2 SOL
-    const voteFee = new anchor.BN(50000000); // 0.05 SOL per token
-
-    await program.methods
-      .createContest(
-        Array.from(contestId),
-        "Voting Test Contest",
-        "Testing voting functionality",
-        startTime,
-        endTime,
-        entryFee,
-        voteFee
-      )
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-        treasury: treasuryPDA,
-        platformConfig: platformConfigPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([payer])
-      .rpc();
-
-    // Update contest status to Live
-    await program.methods
-      .updateContestStatus({ live: {} })
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-      })
-      .signers([payer])
-      .rpc();
-
-    // Find PDAs for entries
-    const [e1PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("entry"), contestPDA.toBuffer(), participant1.publicKey.toBuffer()],
-      program.programId
-    );
-    entryPDA1 = e1PDA;
+    platformConfigPDA = await initializePlatform(program, payer);
+
+    contest = await TestContest.create(bank, payer, platformConfigPDA, {
+      title: "Voting Test Contest",
+      description: "Testing voting functionality",
+    });
+    contestPDA = contest.contest;
+    treasuryPDA = contest.treasury;
+
+    // Submit entries, voting needs at least three
+    await contest.open();
+    entryPDA1 = await contest.submitEntry(participant1, "https://example.com/entry1");
+    entryPDA2 = await contest.submitEntry(participant2, "https://example.com/entry2");
+    await contest.submitEntry(participant3, "https://example.com/entry3");
+
+    // Update contest status to Voting once entries close
+    await contest.startVoting();
+
+    // Find PDAs for votes and the per-contest voter record
+    votePDA1 = contest.voteAddress(voter1.publicKey, entryPDA1);
+    votePDA2 = contest.voteAddress(voter2.publicKey, entryPDA2);
+    voterRecordPDA1 = contest.voterRecordAddress(voter1.publicKey);
+  });
 
-    const [e2PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("entry"), contestPDA.toBuffer(), participant2.publicKey.toBuffer()],
+  it("Freezes entry content once voting starts", async () => {
+    const [entryRevisionsPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("entry-revisions"), entryPDA1.toBuffer()],
       program.programId
     );
-    entryPDA2 = e2PDA;
 
-    // Submit entries
-    await program.methods
-      .submitEntry("https://example.com/entry1")
-      .accounts({
-        participant: participant1.publicKey,
-        contest: contestPDA,
-        entry: entryPDA1,
-        treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([participant1])
-      .rpc();
-
-    await program.methods
-      .submitEntry("https://example.com/entry2")
-      .accounts({
-        participant: participant2.publicKey,
-        contest: contestPDA,
-        entry: entryPDA2,
-        treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([participant2])
-      .rpc();
-
-    // Update contest status to Voting
-    await program.methods
-      .updateContestStatus({ voting: {} })
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-      })
-      .signers([payer])
-      .rpc();
+    try {
+      await program.methods
+        .updateEntry(
+          "https://example.com/swapped",
+          Array.from(createHash("sha256").update("swapped content").digest())
+        )
+        .accountsPartial({
+          participant: participant1.publicKey,
+          contest: contestPDA,
+          platformConfig: platformConfigPDA,
+          entry: entryPDA1,
+          entryRevisions: entryRevisionsPDA,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([participant1])
+        .rpc();
+      expect.fail("update_entry should be rejected during voting");
+    } catch (error) {
+      expect(error.toString()).to.contain("EntryLocked");
+    }
 
-    // Find PDAs for votes
-    const [v1PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("vote"), voter1.publicKey.toBuffer(), entryPDA1.toBuffer()],
-      program.programId
-    );
-    votePDA1 = v1PDA;
+    const entry = await program.account.entryAccount.fetch(entryPDA1);
+    expect(entry.contentUri).to.equal("https://example.com/entry1");
+    expect(entry.contentHash).to.deep.equal(contentHash);
+  });
 
-    const [v2PDA] = await PublicKey.findProgramAddress(
-      [Buffer.from("vote"), voter2.publicKey.toBuffer(), entryPDA2.toBuffer()],
-      program.programId
-    );
-    votePDA2 = v2PDA;
+  it("Rejects a zero-amount vote", async () => {
+    try {
+      await contest.castVote(voter1, entryPDA1, new anchor.BN(0));
+      expect.fail("cast_vote should reject a vote for zero tokens");
+    } catch (error) {
+      expect(error.toString()).to.contain("InvalidVoteAmount");
+    }
   });
 
   it("Casts votes for entries", async () => {
     // Voter 1 votes for entry 1 with 2 tokens
     const tokenAmount1 = new anchor.BN(2);
-
-    await program.methods
-      .castVote(tokenAmount1)
-      .accounts({
-        voter: voter1.publicKey,
-        contest: contestPDA,
-        entry: entryPDA1,
-        vote: votePDA1,
-        treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([voter1])
-      .rpc();
+    await contest.castVote(voter1, entryPDA1, tokenAmount1);
 
     // Voter 2 votes for entry 2 with 3 tokens
     const tokenAmount2 = new anchor.BN(3);
-
-    await program.methods
-      .castVote(tokenAmount2)
-      .accounts({
-        voter: voter2.publicKey,
-        contest: contestPDA,
-        entry: entryPDA2,
-        vote: votePDA2,
-        treasury: treasuryPDA,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([voter2])
-      .rpc();
+    await contest.castVote(voter2, entryPDA2, tokenAmount2);
 
     // Check vote records
     const vote1 = await program.account.voteAccount.fetch(votePDA1);
@@ -221,13 +137,12 @@ describe("Voting Tests", () => {
     expect(entry2.weightedVotes.toString()).to.equal(tokenAmount2.toString());
 
     // Check contest vote count
-    const contest = await program.account.contestAccount.fetch(contestPDA);
-    expect(contest.voteCount).to.equal(2);
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.voteCount).to.equal(2);
 
     // Check treasury
     const treasury = await program.account.treasuryAccount.fetch(treasuryPDA);
-    const entryFees = new anchor.BN(200000000).mul(new anchor.BN(2)); // 2 entries at 0.2 SOL
-    const voteFee = new anchor.BN(50000000); // 0.05 SOL per token
+    const entryFees = entryFee.mul(new anchor.BN(3)); // 3 entries at 0.2 SOL
     const vote1Fees = voteFee.mul(tokenAmount1);
     const vote2Fees = voteFee.mul(tokenAmount2);
     const expectedTotalFunds = entryFees.add(vote1Fees).add(vote2Fees);
@@ -235,43 +150,46 @@ describe("Voting Tests", () => {
     expect(treasury.totalFunds.toString()).to.equal(expectedTotalFunds.toString());
   });
 
+  it("Rejects a second vote in a single-vote contest", async () => {
+    // Voter 1 already voted for entry 1 and tries to vote for entry 2
+    try {
+      await contest.castVote(voter1, entryPDA2);
+      expect.fail("cast_vote should reject votes beyond the contest limit");
+    } catch (error) {
+      expect(error.toString()).to.contain("AlreadyVoted");
+    }
+
+    const voterRecord = await program.account.voterRecord.fetch(voterRecordPDA1);
+    expect(voterRecord.votesCast).to.equal(1);
+  });
+
   it("Counts votes and finalizes contest", async () => {
-    // Update contest status to Closed
-    await program.methods
-      .updateContestStatus({ closed: {} })
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-      })
-      .signers([payer])
-      .rpc();
+    // Update contest status to Closed once voting ends
+    await contest.close();
+
+    // Counting fails unless every entry is passed
+    try {
+      await contest.countVotes([entryPDA1]);
+      expect.fail("count_votes should fail when entries are missing");
+    } catch (error) {
+      expect(error.toString()).to.contain("EntryCountMismatch");
+    }
 
-    // Count votes
-    await program.methods
-      .countVotes()
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-      })
-      .signers([payer])
-      .rpc();
+    // Count votes, passing every entry of the contest
+    await contest.countVotes();
 
     // Check that winners have been decided
-    const contest = await program.account.contestAccount.fetch(contestPDA);
-    expect(contest.winnersDecided).to.equal(true);
+    const contestAccount = await contest.fetch();
+    expect(contestAccount.winnersDecided).to.equal(true);
+
+    // Entry 2 received more weighted votes than entry 1, entry 3 none
+    expect(await contest.ranks()).to.deep.equal([2, 1, 3]);
 
     // Update contest status to Finalized
-    await program.methods
-      .updateContestStatus({ finalized: {} })
-      .accounts({
-        authority: payer.publicKey,
-        contest: contestPDA,
-      })
-      .signers([payer])
-      .rpc();
+    await contest.finalize();
 
     // Fetch final contest status
-    const finalContest = await program.account.contestAccount.fetch(contestPDA);
+    const finalContest = await contest.fetch();
     expect(finalContest.status).to.deep.equal({ finalized: {} });
   });
 });